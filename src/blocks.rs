@@ -0,0 +1,291 @@
+use std::{fmt, fs, io, path::Path};
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+use crate::{logging::target, world::BlockId};
+
+/// Where block types are loaded from, relative to the working directory.
+pub const BLOCKS_PATH: &str = "assets/blocks.ron";
+
+/// Atlas tiles for each face of a block.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum FaceTextures {
+	/// The same tile on every face.
+	All(u32),
+	/// Separate tiles for the top, bottom and sides, like a log.
+	Column { top: u32, bottom: u32, side: u32 },
+	/// A separate tile for every face, named by the direction it faces.
+	Faces {
+		pos_x: u32,
+		neg_x: u32,
+		pos_y: u32,
+		neg_y: u32,
+		pos_z: u32,
+		neg_z: u32,
+	},
+}
+
+impl FaceTextures {
+	/// The tile for the face whose normal runs along `axis` (0 for X, 1 for
+	/// Y, 2 for Z), towards the positive end if `positive`.
+	pub fn tile(&self, axis: usize, positive: bool) -> u32 {
+		match *self {
+			FaceTextures::All(tile) => tile,
+			FaceTextures::Column { top, .. } if axis == 1 && positive => top,
+			FaceTextures::Column { bottom, .. } if axis == 1 => bottom,
+			FaceTextures::Column { side, .. } => side,
+			FaceTextures::Faces {
+				pos_x,
+				neg_x,
+				pos_y,
+				neg_y,
+				pos_z,
+				neg_z,
+			} => match (axis, positive) {
+				(0, true) => pos_x,
+				(0, false) => neg_x,
+				(1, true) => pos_y,
+				(1, false) => neg_y,
+				(_, true) => pos_z,
+				(_, false) => neg_z,
+			},
+		}
+	}
+}
+
+fn default_solid() -> bool {
+	true
+}
+
+/// A block type as described in the blocks file.
+#[derive(Clone, Debug, Deserialize)]
+pub struct BlockType {
+	pub id: u16,
+	pub name: String,
+	pub textures: FaceTextures,
+	/// Blocks behind this one can be seen through it. For now this only
+	/// affects face culling, chunks are drawn in a single opaque pass with
+	/// alpha cut-out and no blending.
+	#[serde(default)]
+	pub transparent: bool,
+	/// Entities collide with this block.
+	// Part of the blocks file format, nothing collides with blocks yet
+	#[allow(dead_code)]
+	#[serde(default = "default_solid")]
+	pub solid: bool,
+	/// Light level emitted, from 0 to 15.
+	// Part of the blocks file format, there is no voxel lighting yet
+	#[allow(dead_code)]
+	#[serde(default)]
+	pub light: u8,
+}
+
+#[derive(Debug)]
+pub enum RegistryError {
+	Io(io::Error),
+	Parse(ron::error::SpannedError),
+	/// Id 0 belongs to air.
+	AirId(String),
+	DuplicateId(u16),
+	DuplicateName(String),
+}
+
+impl fmt::Display for RegistryError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			RegistryError::Io(e) => write!(f, "{}", e),
+			RegistryError::Parse(e) => write!(f, "{}", e),
+			RegistryError::AirId(name) => write!(f, "block {:?} uses id 0, which is air", name),
+			RegistryError::DuplicateId(id) => write!(f, "id {} is used more than once", id),
+			RegistryError::DuplicateName(name) => {
+				write!(f, "name {:?} is used more than once", name)
+			}
+		}
+	}
+}
+
+impl std::error::Error for RegistryError {}
+
+impl From<io::Error> for RegistryError {
+	fn from(e: io::Error) -> Self {
+		RegistryError::Io(e)
+	}
+}
+
+impl From<ron::error::SpannedError> for RegistryError {
+	fn from(e: ron::error::SpannedError) -> Self {
+		RegistryError::Parse(e)
+	}
+}
+
+/// Every known block type, looked up by `BlockId` or by name.
+#[derive(Resource, Default)]
+pub struct BlockRegistry {
+	/// Indexed by id, gaps in the ids are `None`.
+	types: Vec<Option<BlockType>>,
+	names: HashMap<String, BlockId>,
+}
+
+impl BlockRegistry {
+	pub fn from_ron(source: &str) -> Result<Self, RegistryError> {
+		let mut registry = Self::default();
+		for block_type in ron::from_str::<Vec<BlockType>>(source)? {
+			registry.insert(block_type)?;
+		}
+		Ok(registry)
+	}
+
+	pub fn load(path: impl AsRef<Path>) -> Result<Self, RegistryError> {
+		Self::from_ron(&fs::read_to_string(path)?)
+	}
+
+	/// Load the registry at `path`, or an empty one if it can't be read.
+	/// Unknown blocks still render, with the first atlas tile.
+	pub fn load_or_default(path: impl AsRef<Path>) -> Self {
+		let path = path.as_ref();
+		match Self::load(path) {
+			Ok(registry) => registry,
+			Err(e) => {
+				bevy::log::error!(
+					target: target::APP,
+					"Failed to load block types from {}: {}",
+					path.display(),
+					e
+				);
+				Self::default()
+			}
+		}
+	}
+
+	fn insert(&mut self, block_type: BlockType) -> Result<(), RegistryError> {
+		let id = BlockId(block_type.id);
+		if id.is_air() {
+			return Err(RegistryError::AirId(block_type.name));
+		}
+		if self.get(id).is_some() {
+			return Err(RegistryError::DuplicateId(block_type.id));
+		}
+		if self.names.contains_key(&block_type.name) {
+			return Err(RegistryError::DuplicateName(block_type.name));
+		}
+
+		let index = id.0 as usize;
+		if self.types.len() <= index {
+			self.types.resize(index + 1, None);
+		}
+		self.names.insert(block_type.name.clone(), id);
+		self.types[index] = Some(block_type);
+		Ok(())
+	}
+
+	/// The type of `id`, `None` for air and unknown ids.
+	pub fn get(&self, id: BlockId) -> Option<&BlockType> {
+		self.types.get(id.0 as usize)?.as_ref()
+	}
+
+	pub fn id(&self, name: &str) -> Option<BlockId> {
+		self.names.get(name).copied()
+	}
+
+	/// Whether `id` hides the faces of blocks behind it. Unknown blocks are
+	/// treated as opaque.
+	pub fn is_opaque(&self, id: BlockId) -> bool {
+		!id.is_air() && self.get(id).is_none_or(|block| !block.transparent)
+	}
+
+	/// Atlas tile for a face of `id`, see `FaceTextures::tile`.
+	pub fn tile(&self, id: BlockId, axis: usize, positive: bool) -> u32 {
+		self.get(id)
+			.map_or(0, |block| block.textures.tile(axis, positive))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn loads_block_types() {
+		let registry = BlockRegistry::from_ron(
+			r#"[
+				(id: 1, name: "stone", textures: All(0)),
+				(id: 3, name: "water", textures: All(5), transparent: true),
+			]"#,
+		)
+		.unwrap_or_else(|e| panic!("{}", e));
+		let water = registry.id("water").unwrap();
+		assert_eq!(water, BlockId(3));
+		assert!(registry.is_opaque(BlockId(1)));
+		assert!(!registry.is_opaque(water));
+		assert!(registry.get(BlockId(2)).is_none());
+	}
+
+	#[test]
+	fn defaults_to_solid_and_unlit() {
+		let registry = BlockRegistry::from_ron(r#"[(id: 1, name: "stone", textures: All(0))]"#)
+			.unwrap_or_else(|e| panic!("{}", e));
+		let stone = registry.get(BlockId(1)).unwrap();
+		assert!(stone.solid);
+		assert_eq!(stone.light, 0);
+	}
+
+	#[test]
+	fn rejects_air_id() {
+		let result = BlockRegistry::from_ron(r#"[(id: 0, name: "void", textures: All(0))]"#);
+		assert!(matches!(result, Err(RegistryError::AirId(name)) if name == "void"));
+	}
+
+	#[test]
+	fn rejects_duplicate_id() {
+		let result = BlockRegistry::from_ron(
+			r#"[(id: 1, name: "stone", textures: All(0)), (id: 1, name: "dirt", textures: All(2))]"#,
+		);
+		assert!(matches!(result, Err(RegistryError::DuplicateId(1))));
+	}
+
+	#[test]
+	fn rejects_duplicate_name() {
+		let result = BlockRegistry::from_ron(
+			r#"[(id: 1, name: "stone", textures: All(0)), (id: 2, name: "stone", textures: All(2))]"#,
+		);
+		assert!(matches!(result, Err(RegistryError::DuplicateName(name)) if name == "stone"));
+	}
+
+	#[test]
+	fn column_textures_pick_top_bottom_and_side() {
+		let textures = FaceTextures::Column {
+			top: 1,
+			bottom: 2,
+			side: 3,
+		};
+		assert_eq!(textures.tile(1, true), 1);
+		assert_eq!(textures.tile(1, false), 2);
+		for axis in [0, 2] {
+			assert_eq!(textures.tile(axis, true), 3);
+			assert_eq!(textures.tile(axis, false), 3);
+		}
+	}
+
+	#[test]
+	fn per_face_textures_pick_each_face() {
+		let textures = FaceTextures::Faces {
+			pos_x: 1,
+			neg_x: 2,
+			pos_y: 3,
+			neg_y: 4,
+			pos_z: 5,
+			neg_z: 6,
+		};
+		for (axis, positive, tile) in [
+			(0, true, 1),
+			(0, false, 2),
+			(1, true, 3),
+			(1, false, 4),
+			(2, true, 5),
+			(2, false, 6),
+		] {
+			assert_eq!(textures.tile(axis, positive), tile);
+		}
+	}
+}
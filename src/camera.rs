@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+
+/// A point of view the world is rendered from. Each view has its own.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+	pub position: Vec3,
+	/// Rotation from looking down -Z with +Y up.
+	pub rotation: Quat,
+	/// Vertical field of view in radians.
+	pub fov: f32,
+	pub near: f32,
+	pub far: f32,
+}
+
+impl Default for Camera {
+	fn default() -> Self {
+		Self {
+			position: Vec3::new(0.0, 32.0, 64.0),
+			rotation: Quat::from_rotation_x(-0.4),
+			fov: 70.0_f32.to_radians(),
+			near: 0.1,
+			far: 1000.0,
+		}
+	}
+}
+
+impl Camera {
+	pub fn view(&self) -> Mat4 {
+		Mat4::from_rotation_translation(self.rotation, self.position).inverse()
+	}
+
+	/// Perspective projection into Vulkan's clip space, where +Y points down
+	/// and depth runs from 0 to 1.
+	pub fn projection(&self, aspect_ratio: f32) -> Mat4 {
+		let mut projection = Mat4::perspective_rh(self.fov, aspect_ratio, self.near, self.far);
+		projection.y_axis.y = -projection.y_axis.y;
+		projection
+	}
+
+	pub fn view_projection(&self, aspect_ratio: f32) -> Mat4 {
+		self.projection(aspect_ratio) * self.view()
+	}
+}
@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use bevy::{
+	log::{error, warn},
+	window::WindowMode,
+};
+use clap::Parser;
+use vulkano::instance::debug::{
+	DebugUtilsMessageSeverity, DebugUtilsMessageType, DebugUtilsMessengerCallback,
+	DebugUtilsMessengerCreateInfo,
+};
+use vulkano_util::context::VulkanoConfig;
+
+use crate::{
+	logging::{self, target},
+	settings::GraphicsSettings,
+};
+
+/// Launch options, these take precedence over the defaults.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Args {
+	/// Start in borderless fullscreen
+	#[arg(long)]
+	pub fullscreen: bool,
+	/// Enable the Vulkan validation layers and log their messages
+	#[arg(long)]
+	pub validation: bool,
+	/// Render with VK_KHR_dynamic_rendering instead of render pass objects,
+	/// requires a device that supports it
+	#[arg(long)]
+	pub dynamic_rendering: bool,
+	/// Cap the frame rate
+	#[arg(long)]
+	pub fps: Option<u32>,
+	/// Write rendered frames to a numbered PNG sequence in this directory,
+	/// with a fixed simulation timestep
+	#[arg(long, value_name = "DIR")]
+	pub dump_frames: Option<PathBuf>,
+	/// Only dump every Nth frame
+	#[arg(long, value_name = "N", default_value_t = 1)]
+	pub dump_every: u64,
+	/// Simulation rate while dumping frames
+	#[arg(long, value_name = "FPS", default_value_t = 60.0, value_parser = positive_fps)]
+	pub dump_fps: f64,
+	/// Log filter, e.g. `info,render=debug`
+	#[arg(long = "log", env = "RUST_LOG", default_value = logging::DEFAULT_FILTER)]
+	pub log_filter: String,
+}
+
+impl Args {
+	pub fn apply_to(&self, settings: &mut GraphicsSettings) {
+		if self.fullscreen {
+			settings.mode = WindowMode::BorderlessFullscreen;
+		}
+		if let Some(fps) = self.fps {
+			settings.fps_limit = Some(fps);
+		}
+	}
+
+	pub fn vulkano_config(&self) -> VulkanoConfig {
+		let mut config = VulkanoConfig::default();
+		if self.dynamic_rendering {
+			config.device_extensions.khr_dynamic_rendering = true;
+			config.device_features.dynamic_rendering = true;
+		}
+		if self.validation {
+			config
+				.instance_create_info
+				.enabled_layers
+				.push("VK_LAYER_KHRONOS_validation".to_owned());
+			config
+				.instance_create_info
+				.enabled_extensions
+				.ext_debug_utils = true;
+			config.debug_create_info = Some(DebugUtilsMessengerCreateInfo {
+				message_severity: DebugUtilsMessageSeverity::ERROR
+					| DebugUtilsMessageSeverity::WARNING,
+				message_type: DebugUtilsMessageType::GENERAL
+					| DebugUtilsMessageType::VALIDATION
+					| DebugUtilsMessageType::PERFORMANCE,
+				..DebugUtilsMessengerCreateInfo::user_callback(unsafe {
+					DebugUtilsMessengerCallback::new(|severity, _ty, data| {
+						if severity.intersects(DebugUtilsMessageSeverity::ERROR) {
+							error!(target: target::VULKAN, "{}", data.message);
+						} else {
+							warn!(target: target::VULKAN, "{}", data.message);
+						}
+					})
+				})
+			});
+		}
+		config
+	}
+}
+
+fn positive_fps(arg: &str) -> Result<f64, String> {
+	let fps: f64 = arg.parse().map_err(|e| format!("{}", e))?;
+	if fps > 0.0 && fps.is_finite() {
+		Ok(fps)
+	} else {
+		Err("must be greater than zero".to_owned())
+	}
+}
@@ -0,0 +1,57 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{input::mouse::MouseMotion, prelude::*, window::PrimaryWindow};
+
+use crate::{settings::ControlSettings, views::Views};
+
+/// Keep just shy of straight up or down so yaw stays meaningful.
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+/// Fly the camera of the primary window's first view around. WASD moves
+/// relative to where the camera faces, space and control move straight up and
+/// down, and shift speeds everything up. Holding the right mouse button turns
+/// the camera with the mouse.
+pub fn fly_camera(
+	time: Res<Time>,
+	keys: Res<Input<KeyCode>>,
+	buttons: Res<Input<MouseButton>>,
+	mut motion: EventReader<MouseMotion>,
+	settings: Res<ControlSettings>,
+	mut views: Query<&mut Views, With<PrimaryWindow>>,
+) {
+	let delta: Vec2 = motion.read().map(|motion| motion.delta).sum();
+	let Ok(mut views) = views.get_single_mut() else {
+		return;
+	};
+	let Some(camera) = views.0.first_mut().map(|view| &mut view.camera) else {
+		return;
+	};
+	if buttons.pressed(MouseButton::Right) && delta != Vec2::ZERO {
+		let (yaw, pitch, _) = camera.rotation.to_euler(EulerRot::YXZ);
+		let yaw = yaw - delta.x * settings.mouse_sensitivity;
+		let pitch = (pitch - delta.y * settings.mouse_sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+		camera.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
+	}
+
+	let forward = camera.rotation * Vec3::NEG_Z;
+	let right = camera.rotation * Vec3::X;
+	let mut direction = Vec3::ZERO;
+	for (key, towards) in [
+		(KeyCode::W, forward),
+		(KeyCode::S, -forward),
+		(KeyCode::D, right),
+		(KeyCode::A, -right),
+		(KeyCode::Space, Vec3::Y),
+		(KeyCode::ControlLeft, Vec3::NEG_Y),
+	] {
+		if keys.pressed(key) {
+			direction += towards;
+		}
+	}
+
+	let mut speed = settings.fly_speed;
+	if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+		speed *= settings.boost_multiplier;
+	}
+	camera.position += direction.normalize_or_zero() * speed * time.delta_seconds();
+}
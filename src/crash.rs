@@ -0,0 +1,101 @@
+use std::{
+	backtrace::Backtrace,
+	fs::{self, File, OpenOptions},
+	io::{self, Write},
+	panic::{self, PanicHookInfo},
+	path::PathBuf,
+	process,
+	sync::OnceLock,
+	thread,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use bevy_vulkano::BevyVulkanoContext;
+
+use crate::logging;
+
+const CRASH_REPORT_DIR: &str = "crash-reports";
+const RECENT_LOG_LINES: usize = 100;
+
+static DEVICE_INFO: OnceLock<String> = OnceLock::new();
+
+/// Chain a hook onto the default panic handler that also writes a crash
+/// report to disk.
+pub fn install_panic_hook() {
+	let default_hook = panic::take_hook();
+	panic::set_hook(Box::new(move |info| {
+		default_hook(info);
+		match write_report(info) {
+			Ok(path) => eprintln!("Crash report written to {}", path.display()),
+			Err(e) => eprintln!("Failed to write crash report: {}", e),
+		}
+	}));
+}
+
+/// Remember which GPU and driver we are running on for crash reports.
+pub fn record_device_info(context: Res<BevyVulkanoContext>) {
+	let properties = context.context.device().physical_device().properties();
+	let info = format!(
+		"{} ({:?})\ndriver: {} {}\napi version: {}",
+		properties.device_name,
+		properties.device_type,
+		properties.driver_name.as_deref().unwrap_or("unknown"),
+		properties.driver_info.as_deref().unwrap_or(""),
+		properties.api_version,
+	);
+	let _ = DEVICE_INFO.set(info);
+}
+
+/// Create a new report file, never overwriting an earlier one. Several
+/// threads can panic within the same millisecond, so a counter is added to
+/// the name until it is unique.
+fn create_report_file() -> io::Result<(PathBuf, File)> {
+	let timestamp = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_millis();
+	fs::create_dir_all(CRASH_REPORT_DIR)?;
+	let name = format!("crash-{}-{}", timestamp, process::id());
+	let mut attempt = 0;
+	loop {
+		let file_name = match attempt {
+			0 => format!("{}.txt", name),
+			n => format!("{}-{}.txt", name, n),
+		};
+		let path = PathBuf::from(CRASH_REPORT_DIR).join(file_name);
+		match OpenOptions::new().write(true).create_new(true).open(&path) {
+			Ok(file) => return Ok((path, file)),
+			Err(e) if e.kind() == io::ErrorKind::AlreadyExists => attempt += 1,
+			Err(e) => return Err(e),
+		}
+	}
+}
+
+fn write_report(info: &PanicHookInfo) -> io::Result<PathBuf> {
+	let (path, mut file) = create_report_file()?;
+	writeln!(
+		file,
+		"{} {}",
+		env!("CARGO_PKG_NAME"),
+		env!("CARGO_PKG_VERSION")
+	)?;
+	writeln!(
+		file,
+		"thread '{}' {}",
+		thread::current().name().unwrap_or("<unnamed>"),
+		info
+	)?;
+	writeln!(
+		file,
+		"\nDevice:\n{}",
+		DEVICE_INFO.get().map_or("unknown", String::as_str)
+	)?;
+	writeln!(file, "\nBacktrace:\n{}", Backtrace::force_capture())?;
+	writeln!(file, "\nRecent log:")?;
+	let lines = logging::recent_lines();
+	for line in &lines[lines.len().saturating_sub(RECENT_LOG_LINES)..] {
+		writeln!(file, "{}", line)?;
+	}
+	Ok(path)
+}
@@ -0,0 +1,79 @@
+use std::{fs, path::PathBuf, time::Duration};
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+use crate::logging::target;
+
+/// Enables frame dumping when `dir` is set.
+pub struct FrameDumpPlugin {
+	pub dir: Option<PathBuf>,
+	pub every: u64,
+	/// Simulation rate while dumping.
+	pub fps: f64,
+}
+
+impl Plugin for FrameDumpPlugin {
+	fn build(&self, app: &mut App) {
+		let Some(dir) = self.dir.clone() else {
+			return;
+		};
+		// Step the simulation by a fixed amount each frame so the output
+		// doesn't depend on how long capturing takes
+		app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+			1.0 / self.fps,
+		)))
+		.insert_resource(FrameDump::new(dir, self.every));
+	}
+}
+
+/// Writes every `every`th rendered frame of the primary window to a numbered
+/// PNG sequence in `dir`.
+#[derive(Resource)]
+pub struct FrameDump {
+	dir: PathBuf,
+	every: u64,
+	frame: u64,
+	written: u64,
+}
+
+impl FrameDump {
+	pub fn new(dir: PathBuf, every: u64) -> Self {
+		if let Err(e) = fs::create_dir_all(&dir) {
+			bevy::log::error!(
+				target: target::APP,
+				"Failed to create frame dump directory {}: {}",
+				dir.display(),
+				e
+			);
+		}
+		Self {
+			dir,
+			every: every.max(1),
+			frame: 0,
+			written: 0,
+		}
+	}
+
+	/// Advance to the next frame, returning true if this one should be dumped.
+	pub fn next_frame(&mut self) -> bool {
+		let dump = self.frame % self.every == 0;
+		self.frame += 1;
+		dump
+	}
+
+	/// Write RGBA8 `texels` of `extent` as the next image in the sequence.
+	pub fn write(&mut self, extent: [u32; 2], texels: &[u8]) {
+		let path = self.dir.join(format!("frame-{:06}.png", self.written));
+		self.written += 1;
+		if let Err(e) =
+			image::save_buffer(&path, texels, extent[0], extent[1], image::ColorType::Rgba8)
+		{
+			bevy::log::error!(
+				target: target::APP,
+				"Failed to write {}: {}",
+				path.display(),
+				e
+			);
+		}
+	}
+}
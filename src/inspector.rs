@@ -0,0 +1,152 @@
+use std::any::TypeId;
+
+use bevy::{ecs::world::EntityWorldMut, prelude::*, utils::HashMap, window::PrimaryWindow};
+use bevy_vulkano::{egui_winit_vulkano::egui, BevyVulkanoWindows};
+
+/// Draws the editable fields of one component type on an entity.
+pub type ComponentEditor = fn(&mut EntityWorldMut, &mut egui::Ui);
+
+/// State of the entity inspector. Components without a registered editor are
+/// listed by name only.
+#[derive(Resource)]
+pub struct Inspector {
+	pub open: bool,
+	selected: Option<Entity>,
+	editors: HashMap<TypeId, ComponentEditor>,
+}
+
+impl Default for Inspector {
+	fn default() -> Self {
+		let mut inspector = Self {
+			open: false,
+			selected: None,
+			editors: HashMap::default(),
+		};
+		inspector.register::<Window>(edit_window);
+		inspector
+	}
+}
+
+impl Inspector {
+	pub fn register<T: Component>(&mut self, editor: ComponentEditor) {
+		self.editors.insert(TypeId::of::<T>(), editor);
+	}
+}
+
+pub fn toggle_inspector(keys: Res<Input<KeyCode>>, mut inspector: ResMut<Inspector>) {
+	if keys.just_pressed(KeyCode::F3) {
+		inspector.open = !inspector.open;
+	}
+}
+
+pub fn inspector_ui(world: &mut World) {
+	if !world.resource::<Inspector>().open {
+		return;
+	}
+	let Ok(window_entity) = world
+		.query_filtered::<Entity, With<PrimaryWindow>>()
+		.get_single(world)
+	else {
+		return;
+	};
+	let Some(ctx) = world
+		.non_send_resource::<BevyVulkanoWindows>()
+		.get_vulkano_window(window_entity)
+		.map(|window| window.gui.context())
+	else {
+		return;
+	};
+
+	let entities: Vec<(Entity, String)> = world
+		.iter_entities()
+		.map(|entity| {
+			let label = match entity.get::<Name>() {
+				Some(name) => format!("{} ({:?})", name, entity.id()),
+				None => format!("{:?}", entity.id()),
+			};
+			(entity.id(), label)
+		})
+		.collect();
+
+	world.resource_scope(|world, mut inspector: Mut<Inspector>| {
+		if let Some(selected) = inspector.selected {
+			if world.get_entity(selected).is_none() {
+				inspector.selected = None;
+			}
+		}
+		let components: Vec<(String, Option<TypeId>)> = inspector
+			.selected
+			.map(|entity| {
+				world
+					.inspect_entity(entity)
+					.into_iter()
+					.map(|info| (info.name().to_owned(), info.type_id()))
+					.collect()
+			})
+			.unwrap_or_default();
+
+		let inspector = &mut *inspector;
+		egui::Window::new("Inspector")
+			.default_width(600.0)
+			.show(&ctx, |ui| {
+				ui.columns(2, |columns| {
+					egui::ScrollArea::vertical().id_source("entities").show(
+						&mut columns[0],
+						|ui| {
+							for (entity, label) in &entities {
+								ui.selectable_value(&mut inspector.selected, Some(*entity), label);
+							}
+						},
+					);
+
+					let Some(mut entity) = inspector.selected.and_then(|e| world.get_entity_mut(e))
+					else {
+						columns[1].label("No entity selected");
+						return;
+					};
+					egui::ScrollArea::vertical().id_source("components").show(
+						&mut columns[1],
+						|ui| {
+							for (name, type_id) in &components {
+								let editor = type_id.and_then(|id| inspector.editors.get(&id));
+								match editor {
+									Some(editor) => {
+										ui.collapsing(name, |ui| editor(&mut entity, ui));
+									}
+									None => {
+										ui.label(name);
+									}
+								}
+							}
+						},
+					);
+				});
+			});
+	});
+}
+
+fn edit_window(entity: &mut EntityWorldMut, ui: &mut egui::Ui) {
+	let Some(mut window) = entity.get_mut::<Window>() else {
+		return;
+	};
+	// Only write back on change so the window isn't marked changed every frame
+	let mut title = window.title.clone();
+	ui.horizontal(|ui| {
+		ui.label("Title");
+		ui.text_edit_singleline(&mut title);
+	});
+	if title != window.title {
+		window.title = title;
+	}
+
+	let mut decorations = window.decorations;
+	ui.checkbox(&mut decorations, "Decorations");
+	if decorations != window.decorations {
+		window.decorations = decorations;
+	}
+	ui.label(format!(
+		"Resolution: {}x{}",
+		window.resolution.width(),
+		window.resolution.height()
+	));
+}
@@ -0,0 +1,211 @@
+use std::{
+	collections::VecDeque,
+	fmt,
+	sync::{Arc, Mutex, OnceLock, TryLockError},
+};
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_vulkano::{egui_winit_vulkano::egui, BevyVulkanoWindows};
+use tracing::{
+	field::{Field, Visit},
+	Event, Level, Subscriber,
+};
+use tracing_subscriber::{
+	layer::{Context, SubscriberExt},
+	util::SubscriberInitExt,
+	EnvFilter, Layer,
+};
+
+/// Log targets for each engine subsystem, pass these as `target:` to the log
+/// macros so they can be filtered individually.
+pub mod target {
+	pub const APP: &str = "app";
+	pub const RENDER: &str = "render";
+	pub const VULKAN: &str = "vulkan";
+}
+
+pub const DEFAULT_FILTER: &str = "info";
+const MAX_LINES: usize = 1000;
+
+static LOG_BUFFER: OnceLock<LogBuffer> = OnceLock::new();
+
+#[derive(Clone)]
+pub struct LogLine {
+	pub level: Level,
+	pub target: String,
+	pub message: String,
+}
+
+impl fmt::Display for LogLine {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{:>5} {}: {}", self.level, self.target, self.message)
+	}
+}
+
+/// The most recent log lines, kept for the log viewer and crash reports.
+#[derive(Clone, Default)]
+pub struct LogBuffer(Arc<Mutex<VecDeque<LogLine>>>);
+
+impl LogBuffer {
+	fn push(&self, line: LogLine) {
+		let mut lines = self.0.lock().unwrap();
+		if lines.len() == MAX_LINES {
+			lines.pop_front();
+		}
+		lines.push_back(line);
+	}
+}
+
+/// Lines logged since startup, oldest first. Called from the panic hook so
+/// this gives up rather than wait if the buffer is locked.
+pub fn recent_lines() -> Vec<String> {
+	let Some(buffer) = LOG_BUFFER.get() else {
+		return Vec::new();
+	};
+	let lines = match buffer.0.try_lock() {
+		Ok(lines) => lines,
+		Err(TryLockError::Poisoned(e)) => e.into_inner(),
+		Err(TryLockError::WouldBlock) => return Vec::new(),
+	};
+	lines.iter().map(ToString::to_string).collect()
+}
+
+/// Install the global log subscriber. `filter` uses `RUST_LOG` syntax,
+/// e.g. `info,render=debug`.
+pub fn init(filter: &str) {
+	let filter = EnvFilter::try_new(filter).unwrap_or_else(|e| {
+		eprintln!("Invalid log filter {:?}: {}", filter, e);
+		EnvFilter::new(DEFAULT_FILTER)
+	});
+	let buffer = LOG_BUFFER.get_or_init(LogBuffer::default).clone();
+	tracing_subscriber::registry()
+		.with(filter)
+		.with(tracing_subscriber::fmt::layer())
+		.with(BufferLayer(buffer))
+		.init();
+}
+
+struct BufferLayer(LogBuffer);
+
+impl<S: Subscriber> Layer<S> for BufferLayer {
+	fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+		let mut visitor = MessageVisitor(String::new());
+		event.record(&mut visitor);
+		self.0.push(LogLine {
+			level: *event.metadata().level(),
+			target: event.metadata().target().to_owned(),
+			message: visitor.0,
+		});
+	}
+}
+
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+	fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+		use std::fmt::Write;
+		if field.name() == "message" {
+			let _ = write!(self.0, "{:?}", value);
+		} else {
+			let _ = write!(self.0, " {}={:?}", field.name(), value);
+		}
+	}
+}
+
+/// State of the in-game log viewer.
+#[derive(Resource)]
+pub struct LogViewer {
+	pub open: bool,
+	level: Level,
+	search: String,
+}
+
+impl Default for LogViewer {
+	fn default() -> Self {
+		Self {
+			open: false,
+			level: Level::INFO,
+			search: String::new(),
+		}
+	}
+}
+
+pub fn toggle_log_viewer(keys: Res<Input<KeyCode>>, mut viewer: ResMut<LogViewer>) {
+	if keys.just_pressed(KeyCode::F2) {
+		viewer.open = !viewer.open;
+	}
+}
+
+pub fn log_viewer_ui(
+	window_query: Query<Entity, With<PrimaryWindow>>,
+	vulkano_windows: NonSend<BevyVulkanoWindows>,
+	mut viewer: ResMut<LogViewer>,
+) {
+	if !viewer.open {
+		return;
+	}
+	let Some(buffer) = LOG_BUFFER.get() else {
+		return;
+	};
+	let Ok(window_entity) = window_query.get_single() else {
+		return;
+	};
+	let Some(vulkano_window) = vulkano_windows.get_vulkano_window(window_entity) else {
+		return;
+	};
+	let ctx = vulkano_window.gui.context();
+
+	// Copy the lines out rather than hold the lock while drawing, anything
+	// logged from this thread in the meantime would deadlock
+	let lines: Vec<LogLine> = buffer
+		.0
+		.lock()
+		.unwrap()
+		.iter()
+		.filter(|line| {
+			// Levels compare by verbosity, ERROR being the least verbose
+			line.level <= viewer.level
+				&& (viewer.search.is_empty()
+					|| line.message.contains(&viewer.search)
+					|| line.target.contains(&viewer.search))
+		})
+		.cloned()
+		.collect();
+
+	let viewer = &mut *viewer;
+	egui::Window::new("Log")
+		.default_width(800.0)
+		.show(&ctx, |ui| {
+			ui.horizontal(|ui| {
+				egui::ComboBox::from_id_source("log_level")
+					.selected_text(viewer.level.as_str())
+					.show_ui(ui, |ui| {
+						for level in [
+							Level::ERROR,
+							Level::WARN,
+							Level::INFO,
+							Level::DEBUG,
+							Level::TRACE,
+						] {
+							ui.selectable_value(&mut viewer.level, level, level.as_str());
+						}
+					});
+				ui.label("Search");
+				ui.text_edit_singleline(&mut viewer.search);
+			});
+			ui.separator();
+
+			egui::ScrollArea::vertical()
+				.stick_to_bottom(true)
+				.show(ui, |ui| {
+					for line in &lines {
+						let color = match line.level {
+							Level::ERROR => egui::Color32::LIGHT_RED,
+							Level::WARN => egui::Color32::YELLOW,
+							_ => ui.visuals().text_color(),
+						};
+						ui.colored_label(color, line.to_string());
+					}
+				});
+		});
+}
@@ -1,19 +1,34 @@
-use bevy::{
-	app::PluginGroupBuilder,
-	prelude::*,
-	window::{close_on_esc, WindowMode},
-};
+use bevy::{app::PluginGroupBuilder, ecs::system::SystemParam, prelude::*, window::close_on_esc};
 use bevy_vulkano::{
 	BevyVulkanoContext, BevyVulkanoSettings, BevyVulkanoWindows, VulkanoWinitPlugin,
 };
+use clap::Parser;
+use schedule::EngineSet;
 
+mod blocks;
+mod camera;
+mod cli;
+mod controller;
+mod crash;
+mod dump;
+mod inspector;
+mod logging;
+mod mesher;
+mod pacing;
 mod render;
+mod schedule;
+mod settings;
+mod shutdown;
+mod texture;
+mod views;
+mod world;
 
 pub struct PluginBundle;
 
 impl PluginGroup for PluginBundle {
 	fn build(self) -> PluginGroupBuilder {
 		PluginGroupBuilder::start::<PluginBundle>()
+			.add(bevy::time::TimePlugin)
 			.add(bevy::input::InputPlugin)
 			.add(bevy::window::WindowPlugin::default())
 			.add(VulkanoWinitPlugin)
@@ -21,67 +36,161 @@ impl PluginGroup for PluginBundle {
 }
 
 fn main() {
+	crash::install_panic_hook();
+	let args = cli::Args::parse();
+	logging::init(&args.log_filter);
+	let mut graphics_settings = settings::GraphicsSettings::default();
+	args.apply_to(&mut graphics_settings);
+
 	App::new()
 		.insert_non_send_resource(BevyVulkanoSettings {
+			vulkano_config: args.vulkano_config(),
 			is_gui_overlay: true,
 			..BevyVulkanoSettings::default()
 		})
 		.add_plugins(PluginBundle.set(WindowPlugin {
-			primary_window: Some(Window {
-				resolution: (1920.0, 1080.0).into(),
-				present_mode: bevy::window::PresentMode::Fifo,
-				resizable: true,
-				mode: WindowMode::Windowed,
-				..default()
-			}),
+			primary_window: Some(graphics_settings.window()),
 			..default()
 		}))
-		.add_systems(Startup, create_pipelines)
-		.add_systems(Update, close_on_esc)
-		.add_systems(PostUpdate, main_render_system_primary_window)
+		.add_plugins((schedule::EngineSchedulePlugin, shutdown::ShutdownPlugin))
+		.insert_resource(graphics_settings)
+		.init_resource::<settings::SettingsMenu>()
+		.init_resource::<logging::LogViewer>()
+		.init_resource::<inspector::Inspector>()
+		.init_resource::<WindowRenders>()
+		.init_resource::<pacing::FramePacer>()
+		.init_resource::<world::VoxelWorld>()
+		.init_resource::<mesher::ChunkMeshes>()
+		.init_resource::<settings::ControlSettings>()
+		.insert_resource(texture::TextureAtlas::load_or_fallback(texture::ATLAS_PATH))
+		.insert_resource(blocks::BlockRegistry::load_or_default(blocks::BLOCKS_PATH))
+		.add_systems(
+			Startup,
+			(crash::record_device_info, world::generate_test_terrain),
+		)
+		.add_systems(PreUpdate, views::add_window_views)
+		.add_systems(
+			Update,
+			(
+				close_on_esc,
+				views::cycle_split_screen,
+				(
+					settings::toggle_settings_menu,
+					settings::settings_menu_ui,
+					logging::toggle_log_viewer,
+					logging::log_viewer_ui,
+					inspector::toggle_inspector,
+					inspector::inspector_ui,
+				)
+					.chain(),
+			)
+				.in_set(EngineSet::Input),
+		)
+		.add_systems(Update, controller::fly_camera.in_set(EngineSet::Simulation))
+		.add_systems(Update, mesher::remesh_chunks.in_set(EngineSet::MeshPrep))
+		.add_systems(
+			PostUpdate,
+			(main_render_system, pacing::limit_frame_rate)
+				.chain()
+				.in_set(EngineSet::Render),
+		)
+		.add_plugins(dump::FrameDumpPlugin {
+			dir: args.dump_frames.clone(),
+			every: args.dump_every,
+			fps: args.dump_fps,
+		})
 		.run();
 }
 
-fn create_pipelines(
-	mut commands: Commands,
-	window_query: Query<Entity, With<Window>>,
-	context: Res<BevyVulkanoContext>,
-	windows: NonSend<BevyVulkanoWindows>,
-) {
-	let window_entity = window_query.single();
-	let primary_window = windows.get_vulkano_window(window_entity).unwrap();
+/// Renderers for each open window, keyed by window entity. A window's
+/// renderer is created the first time it is drawn and dropped once the window
+/// closes.
+#[derive(Resource, Default)]
+pub struct WindowRenders(HashMap<Entity, render::Render>);
+
+/// The resources shared by every window's frame.
+#[derive(SystemParam)]
+pub struct SceneParams<'w> {
+	meshes: Res<'w, mesher::ChunkMeshes>,
+	atlas: Res<'w, texture::TextureAtlas>,
+}
 
-	let render = render::Render::new(
-		context.context.memory_allocator().clone(),
-		primary_window.renderer.graphics_queue(),
-		primary_window.renderer.swapchain_format(),
-	);
-	commands.insert_resource(render);
+impl SceneParams<'_> {
+	fn scene<'a>(&'a self, views: &'a views::Views) -> render::Scene<'a> {
+		render::Scene {
+			views: &views.0,
+			meshes: &self.meshes,
+		}
+	}
 }
 
-pub fn main_render_system_primary_window(
-	window_query: Query<Entity, With<Window>>,
+pub fn main_render_system(
+	window_query: Query<(Entity, &Window, &views::Views, Has<PrimaryWindow>)>,
+	context: Res<BevyVulkanoContext>,
 	mut vulkano_windows: NonSendMut<BevyVulkanoWindows>,
-	mut render: ResMut<render::Render>,
+	mut renders: ResMut<WindowRenders>,
+	scene_params: SceneParams,
+	mut frame_dump: Option<ResMut<dump::FrameDump>>,
 ) {
-	if let Ok(window_entity) = window_query.get_single() {
-		let primary_window = vulkano_windows
-			.get_vulkano_window_mut(window_entity)
-			.unwrap();
+	// Closed windows may still have frames in flight
+	if renders
+		.0
+		.keys()
+		.any(|entity| !window_query.contains(*entity))
+	{
+		shutdown::wait_for_gpu(&context);
+		renders.0.retain(|entity, _| window_query.contains(*entity));
+	}
+
+	for (window_entity, bevy_window, views, is_primary) in &window_query {
+		// Nothing is visible and there is no swapchain extent to render at,
+		// the rest of the app keeps ticking
+		if is_minimized(bevy_window) {
+			continue;
+		}
+		let Some(window) = vulkano_windows.get_vulkano_window_mut(window_entity) else {
+			continue;
+		};
+		let render = renders.0.entry(window_entity).or_insert_with(|| {
+			render::Render::new(
+				context.context.memory_allocator().clone(),
+				window.renderer.graphics_queue(),
+				window.renderer.swapchain_format(),
+				&scene_params.atlas,
+			)
+		});
 
 		// Start frame
-		let before = match primary_window.renderer.acquire() {
+		let before = match window.renderer.acquire() {
 			Err(e) => {
-				bevy::log::error!("Failed to start frame: {}", e);
-				return;
+				bevy::log::error!(target: logging::target::RENDER, "Failed to start frame: {}", e);
+				continue;
 			}
 			Ok(f) => f,
 		};
 
-		let final_image = primary_window.renderer.swapchain_image_view();
-		let after_render = render.render(before, final_image);
+		let scene = scene_params.scene(views);
+		let final_image = window.renderer.swapchain_image_view();
+		let mut after_render = render.render(before, final_image.clone(), &scene);
+		if let Some(frame_dump) = frame_dump.as_mut().filter(|_| is_primary) {
+			if frame_dump.next_frame() {
+				let [width, height, _] = final_image.image().extent();
+				let extent = [width, height];
+				let texels = render.capture(extent, &scene);
+				frame_dump.write(extent, &texels);
+			}
+		}
+
+		// Only the primary window runs the UI
+		if is_primary {
+			after_render = window.gui.draw_on_image(after_render, final_image);
+		}
 
 		// Finish Frame
-		primary_window.renderer.present(after_render, true);
+		window.renderer.present(after_render, true);
 	}
 }
+
+fn is_minimized(window: &Window) -> bool {
+	window.physical_width() == 0 || window.physical_height() == 0
+}
@@ -0,0 +1,341 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+	blocks::BlockRegistry,
+	render::backend::{AttributeFormat, VertexAttribute, VertexLayout},
+	world::{BlockId, VoxelWorld, CHUNK_SIZE},
+};
+
+/// Offsets of the chunks whose blocks can hide faces on a chunk's border.
+const NEIGHBOURS: [IVec3; 6] = [
+	IVec3::X,
+	IVec3::NEG_X,
+	IVec3::Y,
+	IVec3::NEG_Y,
+	IVec3::Z,
+	IVec3::NEG_Z,
+];
+
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct ChunkVertex {
+	/// World space position.
+	pub position: [f32; 3],
+	pub normal: [f32; 3],
+	/// Texture coordinates in blocks, tiling once per block.
+	pub uv: [f32; 2],
+	/// Atlas tile to texture the face with.
+	pub tile: u32,
+}
+
+impl ChunkVertex {
+	pub fn layout() -> VertexLayout {
+		VertexLayout {
+			stride: std::mem::size_of::<Self>() as u32,
+			attributes: vec![
+				VertexAttribute {
+					location: 0,
+					format: AttributeFormat::Float3,
+					offset: 0,
+				},
+				VertexAttribute {
+					location: 1,
+					format: AttributeFormat::Float3,
+					offset: 12,
+				},
+				VertexAttribute {
+					location: 2,
+					format: AttributeFormat::Float2,
+					offset: 24,
+				},
+				VertexAttribute {
+					location: 3,
+					format: AttributeFormat::Uint,
+					offset: 32,
+				},
+			],
+		}
+	}
+}
+
+/// Indexed triangle list for the visible faces of one chunk.
+#[derive(Clone, Default)]
+pub struct ChunkMesh {
+	pub vertices: Vec<ChunkVertex>,
+	pub indices: Vec<u32>,
+}
+
+impl ChunkMesh {
+	pub fn is_empty(&self) -> bool {
+		self.indices.is_empty()
+	}
+
+	/// Add a `w` by `h` quad lying in the plane `axes[0] = slice`, spanning
+	/// `axes[1]` and `axes[2]` from `(i, j)`.
+	#[allow(clippy::too_many_arguments)]
+	fn push_quad(
+		&mut self,
+		origin: IVec3,
+		axes: [usize; 3],
+		slice: i32,
+		[i, j]: [i32; 2],
+		[w, h]: [i32; 2],
+		positive: bool,
+		tile: u32,
+	) {
+		let [d, u, v] = axes;
+		let mut base = origin;
+		base[d] += slice;
+		base[u] += i;
+		base[v] += j;
+		let mut du = IVec3::ZERO;
+		du[u] = w;
+		let mut dv = IVec3::ZERO;
+		dv[v] = h;
+		let mut normal = Vec3::ZERO;
+		normal[d] = if positive { 1.0 } else { -1.0 };
+
+		let start = self.vertices.len() as u32;
+		for corner in [base, base + du, base + du + dv, base + dv] {
+			let corner = corner.as_vec3();
+			// Project onto the face's plane so that textures on side faces
+			// are upright
+			let uv = match d {
+				0 => Vec2::new(corner.z, -corner.y),
+				1 => Vec2::new(corner.x, corner.z),
+				_ => Vec2::new(corner.x, -corner.y),
+			};
+			self.vertices.push(ChunkVertex {
+				position: corner.to_array(),
+				normal: normal.to_array(),
+				uv: uv.to_array(),
+				tile,
+			});
+		}
+		// `u` cross `v` points along +`d`, flip the winding for faces that
+		// look the other way so every face is counter-clockwise from outside
+		let order = if positive {
+			[0, 1, 2, 0, 2, 3]
+		} else {
+			[0, 2, 1, 0, 3, 2]
+		};
+		self.indices.extend(order.map(|index| start + index));
+	}
+}
+
+/// Build the mesh for the chunk at `chunk_pos`, merging neighbouring faces of
+/// the same block into larger quads. Faces are only kept where they can be
+/// seen past the neighbouring block, including blocks in neighbouring chunks.
+pub fn mesh_chunk(world: &VoxelWorld, registry: &BlockRegistry, chunk_pos: IVec3) -> ChunkMesh {
+	let mut mesh = ChunkMesh::default();
+	let Some(chunk) = world.chunk(chunk_pos) else {
+		return mesh;
+	};
+	let size = CHUNK_SIZE as i32;
+	let origin = chunk_pos * size;
+	let block_at = |pos: IVec3| {
+		if pos.cmpge(IVec3::ZERO).all() && pos.cmplt(IVec3::splat(size)).all() {
+			chunk.get(pos.as_uvec3())
+		} else {
+			world.get_block(origin + pos)
+		}
+	};
+	// Faces between two of the same transparent block, like water, are
+	// hidden too
+	let visible = |block: BlockId, neighbour: BlockId| {
+		!block.is_air() && block != neighbour && !registry.is_opaque(neighbour)
+	};
+
+	let mut mask: Vec<Option<BlockId>> = vec![None; CHUNK_SIZE * CHUNK_SIZE];
+	let index = |i: i32, j: i32| (i + j * size) as usize;
+	for d in 0..3 {
+		let axes = [d, (d + 1) % 3, (d + 2) % 3];
+		let [_, u, v] = axes;
+		for positive in [true, false] {
+			for layer in 0..size {
+				for j in 0..size {
+					for i in 0..size {
+						let mut pos = IVec3::ZERO;
+						pos[d] = layer;
+						pos[u] = i;
+						pos[v] = j;
+						let mut neighbour = pos;
+						neighbour[d] += if positive { 1 } else { -1 };
+						let block = block_at(pos);
+						mask[index(i, j)] = visible(block, block_at(neighbour)).then_some(block);
+					}
+				}
+
+				// Faces towards +`d` lie on the far side of their layer
+				let slice = if positive { layer + 1 } else { layer };
+				for j in 0..size {
+					let mut i = 0;
+					while i < size {
+						let Some(block) = mask[index(i, j)] else {
+							i += 1;
+							continue;
+						};
+						let mut w = 1;
+						while i + w < size && mask[index(i + w, j)] == Some(block) {
+							w += 1;
+						}
+						let mut h = 1;
+						while j + h < size
+							&& (i..i + w).all(|k| mask[index(k, j + h)] == Some(block))
+						{
+							h += 1;
+						}
+						let tile = registry.tile(block, d, positive);
+						mesh.push_quad(origin, axes, slice, [i, j], [w, h], positive, tile);
+						for y in j..j + h {
+							for x in i..i + w {
+								mask[index(x, y)] = None;
+							}
+						}
+						i += w;
+					}
+				}
+			}
+		}
+	}
+	mesh
+}
+
+/// A built mesh along with what it was built from.
+pub struct MeshedChunk {
+	pub mesh: ChunkMesh,
+	/// The chunk's mesh version once this mesh was built.
+	pub version: u64,
+	/// Data versions of the chunk and then each of `NEIGHBOURS`, zero for
+	/// neighbours that were not loaded.
+	sources: [u64; 7],
+}
+
+/// The latest mesh of every loaded chunk, keyed by chunk coordinates.
+#[derive(Resource, Default)]
+pub struct ChunkMeshes {
+	meshes: HashMap<IVec3, MeshedChunk>,
+}
+
+impl ChunkMeshes {
+	pub fn get(&self, pos: IVec3) -> Option<&MeshedChunk> {
+		self.meshes.get(&pos)
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (IVec3, &MeshedChunk)> {
+		self.meshes.iter().map(|(pos, meshed)| (*pos, meshed))
+	}
+}
+
+fn sources(world: &VoxelWorld, pos: IVec3) -> [u64; 7] {
+	let mut sources = [0; 7];
+	sources[0] = world.chunk(pos).map_or(0, |chunk| chunk.versions().data);
+	for (source, offset) in sources[1..].iter_mut().zip(NEIGHBOURS) {
+		*source = world
+			.chunk(pos + offset)
+			.map_or(0, |chunk| chunk.versions().data);
+	}
+	sources
+}
+
+/// Rebuild the mesh of every chunk whose blocks, or whose neighbours' blocks,
+/// changed since it was last meshed. Everything is rebuilt when the block
+/// types change.
+pub fn remesh_chunks(
+	mut world: ResMut<VoxelWorld>,
+	registry: Res<BlockRegistry>,
+	mut meshes: ResMut<ChunkMeshes>,
+) {
+	if registry.is_changed() {
+		meshes.meshes.clear();
+	}
+
+	let stale: Vec<_> = world
+		.chunks()
+		.map(|(pos, _)| (pos, sources(&world, pos)))
+		.filter(|(pos, sources)| {
+			meshes
+				.meshes
+				.get(pos)
+				.is_none_or(|meshed| meshed.sources != *sources)
+		})
+		.collect();
+
+	for (pos, sources) in stale {
+		let mesh = mesh_chunk(&world, &registry, pos);
+		let Some(chunk) = world.chunk_mut(pos) else {
+			continue;
+		};
+		chunk.mark_mesh_changed();
+		meshes.meshes.insert(
+			pos,
+			MeshedChunk {
+				mesh,
+				version: chunk.versions().mesh,
+				sources,
+			},
+		);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const STONE: BlockId = BlockId(1);
+
+	fn mesh(world: &VoxelWorld, chunk_pos: IVec3) -> ChunkMesh {
+		mesh_chunk(world, &BlockRegistry::default(), chunk_pos)
+	}
+
+	fn quads(mesh: &ChunkMesh) -> usize {
+		assert_eq!(mesh.vertices.len() % 4, 0);
+		assert_eq!(mesh.indices.len(), mesh.vertices.len() / 4 * 6);
+		mesh.vertices.len() / 4
+	}
+
+	#[test]
+	fn single_block_has_six_faces() {
+		let mut world = VoxelWorld::default();
+		world.set_block(IVec3::ZERO, STONE);
+
+		let mesh = mesh(&world, IVec3::ZERO);
+		assert_eq!(quads(&mesh), 6);
+		assert_eq!(mesh.indices.len(), 36);
+	}
+
+	#[test]
+	fn faces_wind_counter_clockwise_from_outside() {
+		let mut world = VoxelWorld::default();
+		world.set_block(IVec3::ZERO, STONE);
+
+		let mesh = mesh(&world, IVec3::ZERO);
+		for triangle in mesh.indices.chunks_exact(3) {
+			let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize]);
+			let [pa, pb, pc] = [a, b, c].map(|v| Vec3::from(v.position));
+			let facing = (pb - pa).cross(pc - pa);
+			assert!(facing.dot(Vec3::from(a.normal)) > 0.0);
+		}
+	}
+
+	#[test]
+	fn slab_merges_into_six_faces() {
+		let mut world = VoxelWorld::default();
+		world.set_block(IVec3::new(0, 0, 0), STONE);
+		world.set_block(IVec3::new(1, 0, 0), STONE);
+
+		let mesh = mesh(&world, IVec3::ZERO);
+		assert_eq!(quads(&mesh), 6);
+	}
+
+	#[test]
+	fn neighbouring_chunks_hide_shared_face() {
+		let size = CHUNK_SIZE as i32;
+		let mut world = VoxelWorld::default();
+		world.set_block(IVec3::new(size - 1, 0, 0), STONE);
+		world.set_block(IVec3::new(size, 0, 0), STONE);
+
+		assert_eq!(quads(&mesh(&world, IVec3::ZERO)), 5);
+		assert_eq!(quads(&mesh(&world, IVec3::X)), 5);
+	}
+}
@@ -0,0 +1,70 @@
+use std::{
+	thread,
+	time::{Duration, Instant},
+};
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::settings::GraphicsSettings;
+
+/// How long before the deadline to stop sleeping and start spinning, sleep
+/// granularity is too coarse on some platforms to hit the deadline exactly.
+const SPIN_MARGIN: Duration = Duration::from_millis(2);
+
+#[derive(Resource)]
+pub struct FramePacer {
+	next_frame: Instant,
+}
+
+impl Default for FramePacer {
+	fn default() -> Self {
+		Self {
+			next_frame: Instant::now(),
+		}
+	}
+}
+
+/// Delay the end of the frame until the frame limit allows the next one to
+/// start. Runs after the frame has been presented.
+pub fn limit_frame_rate(
+	window_query: Query<&Window, With<PrimaryWindow>>,
+	settings: Res<GraphicsSettings>,
+	mut pacer: ResMut<FramePacer>,
+) {
+	let focused = window_query
+		.get_single()
+		.map_or(true, |window| window.focused);
+	let limit = match (focused, settings.unfocused_fps_limit) {
+		(false, Some(unfocused)) => Some(
+			settings
+				.fps_limit
+				.map_or(unfocused, |fps| fps.min(unfocused)),
+		),
+		_ => settings.fps_limit,
+	};
+	let Some(fps) = limit.filter(|fps| *fps > 0) else {
+		pacer.next_frame = Instant::now();
+		return;
+	};
+
+	let frame_time = Duration::from_secs_f64(1.0 / fps as f64);
+	let deadline = pacer.next_frame;
+	let now = Instant::now();
+	if now < deadline {
+		if let Some(sleep) = (deadline - now).checked_sub(SPIN_MARGIN) {
+			thread::sleep(sleep);
+		}
+		while Instant::now() < deadline {
+			std::hint::spin_loop();
+		}
+	}
+
+	// Schedule from the deadline to keep a steady cadence, unless we have
+	// fallen a whole frame behind
+	let now = Instant::now();
+	pacer.next_frame = if now > deadline + frame_time {
+		now + frame_time
+	} else {
+		deadline + frame_time
+	};
+}
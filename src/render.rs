@@ -1,45 +1,45 @@
-use bevy::ecs::system::Resource;
 use std::sync::Arc;
 
+use bevy::{
+	math::{IVec3, Mat4},
+	utils::HashMap,
+};
 use vulkano::{
-	buffer::{Buffer, BufferContents, BufferCreateInfo, BufferUsage, Subbuffer},
-	command_buffer::{
-		allocator::StandardCommandBufferAllocator, AutoCommandBufferBuilder, CommandBufferUsage,
-		RenderPassBeginInfo, SubpassBeginInfo, SubpassContents,
-	},
-	command_buffer::{
-		allocator::StandardCommandBufferAllocatorCreateInfo, CommandBufferInheritanceInfo,
-		SecondaryAutoCommandBuffer,
-	},
-	descriptor_set::allocator::StandardDescriptorSetAllocator,
 	device::{DeviceOwned, Queue},
 	format::Format,
 	image::view::ImageView,
 	memory::allocator::StandardMemoryAllocator,
-	memory::allocator::{AllocationCreateInfo, MemoryTypeFilter},
-	pipeline::{
-		graphics::{
-			color_blend::{ColorBlendAttachmentState, ColorBlendState},
-			input_assembly::InputAssemblyState,
-			multisample::MultisampleState,
-			rasterization::RasterizationState,
-			vertex_input::{Vertex, VertexDefinition},
-			viewport::{Viewport, ViewportState},
-			GraphicsPipelineCreateInfo,
-		},
-		layout::PipelineDescriptorSetLayoutCreateInfo,
-		DynamicState, GraphicsPipeline, PipelineLayout, PipelineShaderStageCreateInfo,
-	},
-	render_pass::{Framebuffer, FramebufferCreateInfo, RenderPass, Subpass},
 	sync::GpuFuture,
 };
 
-#[derive(Resource)]
+use crate::{
+	camera::Camera,
+	mesher::{ChunkMeshes, ChunkVertex},
+	texture::TextureAtlas,
+};
+use backend::{Backend, BufferUsage, RenderState, VulkanoBackend};
+use pipelines::{PipelineCache, ShaderSetId};
+
+pub mod backend;
+pub mod pipelines;
+
+/// Everything a frame is drawn from.
+pub struct Scene<'a> {
+	/// The scene is drawn once into each view.
+	pub views: &'a [View],
+	pub meshes: &'a ChunkMeshes,
+}
+
 pub struct Render {
 	gfx_queue: Arc<Queue>,
-	command_buffer_allocator: StandardCommandBufferAllocator,
-	render_pass: Arc<RenderPass>,
-	triangle_draw_pipeline: TriangleDrawPipeline,
+	backend: VulkanoBackend,
+	output_format: Format,
+	pass: <VulkanoBackend as Backend>::Pass,
+	/// Depth image for `render`, recreated when the target size changes.
+	/// Captures are submitted separately and use their own.
+	depth_target: Option<Arc<ImageView>>,
+	pipelines: PipelineCache<VulkanoBackend>,
+	chunk_draw_pipeline: ChunkDrawPipeline<VulkanoBackend>,
 }
 
 impl Render {
@@ -47,231 +47,233 @@ impl Render {
 		allocator: Arc<StandardMemoryAllocator>,
 		gfx_queue: Arc<Queue>,
 		output_format: Format,
+		atlas: &TextureAtlas,
 	) -> Self {
-		let render_pass = vulkano::single_pass_renderpass!(gfx_queue.device().clone(),
-			attachments: {
-				color: {
-					format: output_format,
-					samples: 1,
-					load_op: Clear,
-					store_op: Store,
-				}
-			},
-			pass: {
-					color: [color],
-					depth_stencil: {}
-			}
-		)
-		.unwrap();
-		let subpass = Subpass::from(render_pass.clone(), 0).unwrap();
+		let device = allocator.device().clone();
+		let backend = VulkanoBackend::new(allocator, gfx_queue.clone());
+		let pass = backend.create_pass(output_format);
 
-		let triangle_draw_pipeline =
-			TriangleDrawPipeline::new(allocator.clone(), gfx_queue.clone(), subpass);
+		let vs = vs::load(device.clone())
+			.expect("failed to create shader module")
+			.entry_point("main")
+			.expect("shader entry point not found");
+		let fs = fs::load(device)
+			.expect("failed to create shader module")
+			.entry_point("main")
+			.expect("shader entry point not found");
+		let mut pipelines = PipelineCache::default();
+		pipelines.register_shaders(CHUNK_SHADERS, vs, fs, ChunkVertex::layout());
+		let atlas = backend.create_texture(atlas.extent(), atlas.texels());
 
 		Self {
 			gfx_queue,
-			command_buffer_allocator: StandardCommandBufferAllocator::new(
-				allocator.device().clone(),
-				Default::default(),
-			),
-			render_pass,
-			triangle_draw_pipeline,
+			backend,
+			output_format,
+			pass,
+			depth_target: None,
+			pipelines,
+			chunk_draw_pipeline: ChunkDrawPipeline::new(atlas),
 		}
 	}
 
-	pub fn render<F>(&mut self, before_future: F, target: Arc<ImageView>) -> Box<dyn GpuFuture>
+	/// Render `scene` into `target`, uploading any chunk meshes that changed
+	/// first.
+	pub fn render<F>(
+		&mut self,
+		before_future: F,
+		target: Arc<ImageView>,
+		scene: &Scene,
+	) -> Box<dyn GpuFuture>
 	where
 		F: GpuFuture + 'static,
 	{
-		let img_dims = target.image().extent();
-		let framebuffer = Framebuffer::new(
-			self.render_pass.clone(),
-			FramebufferCreateInfo {
-				attachments: vec![target],
-				..Default::default()
-			},
-		)
-		.unwrap();
-		let mut command_buffer_builder = AutoCommandBufferBuilder::primary(
-			&self.command_buffer_allocator,
-			self.gfx_queue.queue_family_index(),
-			CommandBufferUsage::OneTimeSubmit,
-		)
-		.unwrap();
-		command_buffer_builder
-			.begin_render_pass(
-				RenderPassBeginInfo {
-					clear_values: vec![Some([0.0; 4].into())],
-					..RenderPassBeginInfo::framebuffer(framebuffer)
-				},
-				SubpassBeginInfo {
-					contents: SubpassContents::SecondaryCommandBuffers,
-					..Default::default()
-				},
-			)
-			.unwrap();
-		let cb = self.triangle_draw_pipeline.draw([img_dims[0], img_dims[1]]);
-		command_buffer_builder.execute_commands(cb).unwrap();
-		command_buffer_builder
-			.end_render_pass(Default::default())
-			.unwrap();
-		let command_buffer = command_buffer_builder.build().unwrap();
+		self.chunk_draw_pipeline.update(&self.backend, scene.meshes);
+		let [width, height, _] = target.image().extent();
+		let depth = self.depth_target([width, height]);
+		let mut commands = self.backend.begin_pass(&self.pass, target, depth, [0.0; 4]);
+		self.draw_views(&mut commands, [width, height], scene);
+		let command_buffer = self.backend.end_pass(commands);
 		let after_future = before_future
 			.then_execute(self.gfx_queue.clone(), command_buffer)
 			.unwrap();
 
 		after_future.boxed()
 	}
+
+	/// Render `scene` into an offscreen image of `extent` and read it back as
+	/// tightly packed RGBA8 texels. Blocks until the GPU has finished.
+	pub fn capture(&mut self, extent: [u32; 2], scene: &Scene) -> Vec<u8> {
+		self.chunk_draw_pipeline.update(&self.backend, scene.meshes);
+		let target = self
+			.backend
+			.create_render_target(self.output_format, extent);
+		// The frame's own depth image may still be in use by the GPU
+		let depth = self.backend.create_depth_target(extent);
+		let mut commands = self
+			.backend
+			.begin_pass(&self.pass, target.clone(), depth, [0.0; 4]);
+		self.draw_views(&mut commands, extent, scene);
+		let command_buffer = self.backend.end_pass(commands);
+		self.backend.submit_and_wait(command_buffer);
+
+		let mut texels = self.backend.read_image(&target);
+		if matches!(
+			self.output_format,
+			Format::B8G8R8A8_SRGB | Format::B8G8R8A8_UNORM
+		) {
+			for texel in texels.chunks_exact_mut(4) {
+				texel.swap(0, 2);
+			}
+		}
+		texels
+	}
+
+	fn depth_target(&mut self, extent: [u32; 2]) -> Arc<ImageView> {
+		if let Some(depth) = &self.depth_target {
+			let [width, height, _] = depth.image().extent();
+			if [width, height] == extent {
+				return depth.clone();
+			}
+		}
+		let depth = self.backend.create_depth_target(extent);
+		self.depth_target = Some(depth.clone());
+		depth
+	}
+
+	fn draw_views(
+		&mut self,
+		commands: &mut <VulkanoBackend as Backend>::Commands,
+		target_extent: [u32; 2],
+		scene: &Scene,
+	) {
+		let target_size = [target_extent[0] as f32, target_extent[1] as f32];
+		for view in scene.views {
+			let (offset, extent) = view.viewport(target_size);
+			let view_projection = view.camera.view_projection(extent[0] / extent[1]);
+			self.chunk_draw_pipeline.draw(
+				&self.backend,
+				&mut self.pipelines,
+				&self.pass,
+				commands,
+				offset,
+				extent,
+				view_projection,
+			);
+		}
+	}
 }
 
-#[derive(BufferContents, Vertex)]
-#[repr(C)]
-struct PosVertex {
-	#[format(R32G32_SFLOAT)]
-	position: [f32; 2],
+/// A region of the render target the scene is drawn into, in fractions of
+/// the target size, and the camera it is drawn from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct View {
+	pub offset: [f32; 2],
+	pub extent: [f32; 2],
+	pub camera: Camera,
 }
 
-impl PosVertex {
-	pub fn new(x: f32, y: f32) -> Self {
-		Self { position: [x, y] }
+impl View {
+	/// A view covering the whole target.
+	pub fn full(camera: Camera) -> Self {
+		Self {
+			offset: [0.0, 0.0],
+			extent: [1.0, 1.0],
+			camera,
+		}
+	}
+
+	/// The viewport offset and extent in pixels for a target of `size`.
+	fn viewport(&self, size: [f32; 2]) -> ([f32; 2], [f32; 2]) {
+		(
+			[self.offset[0] * size[0], self.offset[1] * size[1]],
+			[self.extent[0] * size[0], self.extent[1] * size[1]],
+		)
 	}
 }
 
-fn triangle() -> Vec<PosVertex> {
-	vec![
-		PosVertex::new(0.0, -0.5),
-		PosVertex::new(0.5, 0.5),
-		PosVertex::new(-0.5, 0.5),
-	]
+const CHUNK_SHADERS: ShaderSetId = "chunk";
+
+/// A chunk mesh uploaded to the GPU.
+struct GpuChunk<B: Backend> {
+	/// Mesh version this upload was made from.
+	version: u64,
+	vertices: B::Buffer,
+	indices: B::Buffer,
+	index_count: u32,
 }
 
-pub struct TriangleDrawPipeline {
-	gfx_queue: Arc<Queue>,
-	command_buffer_allocator: StandardCommandBufferAllocator,
-	descriptor_set_allocator: StandardDescriptorSetAllocator,
-	pipeline: Arc<GraphicsPipeline>,
-	subpass: Subpass,
-	vertices: Subbuffer<[PosVertex]>,
+pub struct ChunkDrawPipeline<B: Backend> {
+	state: RenderState,
+	atlas: B::Texture,
+	chunks: HashMap<IVec3, GpuChunk<B>>,
 }
 
-impl TriangleDrawPipeline {
-	pub fn new(
-		allocator: Arc<StandardMemoryAllocator>,
-		gfx_queue: Arc<Queue>,
-		subpass: Subpass,
-	) -> Self {
-		let vertices = triangle();
-		let vertex_buffer = Buffer::from_iter(
-			allocator.clone(),
-			BufferCreateInfo {
-				usage: BufferUsage::VERTEX_BUFFER,
-				..Default::default()
-			},
-			AllocationCreateInfo {
-				memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
-					| MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
-				..Default::default()
-			},
-			vertices,
-		)
-		.unwrap();
-
-		let pipeline = {
-			let vs = vs::load(allocator.device().clone())
-				.expect("failed to create shader module")
-				.entry_point("main")
-				.expect("shader entry point not found");
-			let fs = fs::load(allocator.device().clone())
-				.expect("failed to create shader module")
-				.entry_point("main")
-				.expect("shader entry point not found");
-			let vertex_input_state = PosVertex::per_vertex()
-				.definition(&vs.info().input_interface)
-				.unwrap();
-			let stages = [
-				PipelineShaderStageCreateInfo::new(vs),
-				PipelineShaderStageCreateInfo::new(fs),
-			];
-			let layout = PipelineLayout::new(
-				allocator.device().clone(),
-				PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
-					.into_pipeline_layout_create_info(allocator.device().clone())
-					.unwrap(),
-			)
-			.unwrap();
+impl<B: Backend> ChunkDrawPipeline<B> {
+	pub fn new(atlas: B::Texture) -> Self {
+		Self {
+			state: RenderState::default(),
+			atlas,
+			chunks: HashMap::default(),
+		}
+	}
 
-			GraphicsPipeline::new(
-				allocator.device().clone(),
-				None,
-				GraphicsPipelineCreateInfo {
-					stages: stages.into_iter().collect(),
-					vertex_input_state: Some(vertex_input_state),
-					input_assembly_state: Some(InputAssemblyState::default()),
-					viewport_state: Some(ViewportState::default()),
-					rasterization_state: Some(RasterizationState::default()),
-					multisample_state: Some(MultisampleState::default()),
-					color_blend_state: Some(ColorBlendState::with_attachment_states(
-						subpass.num_color_attachments(),
-						ColorBlendAttachmentState::default(),
-					)),
-					dynamic_state: [DynamicState::Viewport].into_iter().collect(),
-					subpass: Some(subpass.clone().into()),
-					..GraphicsPipelineCreateInfo::layout(layout)
+	/// Upload meshes that changed since the last call and drop the buffers of
+	/// chunks that no longer have one.
+	pub fn update(&mut self, backend: &B, meshes: &ChunkMeshes) {
+		self.chunks.retain(|pos, _| {
+			meshes
+				.get(*pos)
+				.is_some_and(|meshed| !meshed.mesh.is_empty())
+		});
+		for (pos, meshed) in meshes.iter() {
+			// Zero sized buffers are not allowed
+			if meshed.mesh.is_empty()
+				|| self
+					.chunks
+					.get(&pos)
+					.is_some_and(|chunk| chunk.version == meshed.version)
+			{
+				continue;
+			}
+			let mesh = &meshed.mesh;
+			self.chunks.insert(
+				pos,
+				GpuChunk {
+					version: meshed.version,
+					vertices: backend
+						.create_buffer(BufferUsage::Vertex, bytemuck::cast_slice(&mesh.vertices)),
+					indices: backend
+						.create_buffer(BufferUsage::Index, bytemuck::cast_slice(&mesh.indices)),
+					index_count: mesh.indices.len() as u32,
 				},
-			)
-			.unwrap()
-		};
-		let command_buffer_allocator = StandardCommandBufferAllocator::new(
-			allocator.device().clone(),
-			StandardCommandBufferAllocatorCreateInfo {
-				secondary_buffer_count: 32,
-				..Default::default()
-			},
-		);
-		let descriptor_set_allocator =
-			StandardDescriptorSetAllocator::new(allocator.device().clone(), Default::default());
-
-		Self {
-			gfx_queue,
-			command_buffer_allocator,
-			descriptor_set_allocator,
-			pipeline,
-			subpass,
-			vertices: vertex_buffer,
+			);
 		}
 	}
 
-	pub fn draw(&mut self, viewport_dimensions: [u32; 2]) -> Arc<SecondaryAutoCommandBuffer> {
-		let mut builder = AutoCommandBufferBuilder::secondary(
-			&self.command_buffer_allocator,
-			self.gfx_queue.queue_family_index(),
-			CommandBufferUsage::MultipleSubmit,
-			CommandBufferInheritanceInfo {
-				render_pass: Some(self.subpass.clone().into()),
-				..Default::default()
-			},
-		)
-		.unwrap();
-
-		builder
-			.set_viewport(
-				0,
-				[Viewport {
-					offset: [0.0, 0.0],
-					extent: [viewport_dimensions[0] as f32, viewport_dimensions[1] as f32],
-					depth_range: 0.0..=1.0,
-				}]
-				.into_iter()
-				.collect(),
-			)
-			.unwrap()
-			.bind_pipeline_graphics(self.pipeline.clone())
-			.unwrap()
-			.bind_vertex_buffers(0, self.vertices.clone())
-			.unwrap()
-			.draw(self.vertices.len() as u32, 1, 0, 0)
-			.unwrap();
-		builder.build().unwrap()
+	#[allow(clippy::too_many_arguments)]
+	pub fn draw(
+		&self,
+		backend: &B,
+		pipelines: &mut PipelineCache<B>,
+		pass: &B::Pass,
+		commands: &mut B::Commands,
+		viewport_offset: [f32; 2],
+		viewport_extent: [f32; 2],
+		view_projection: Mat4,
+	) {
+		let pipeline = pipelines.get(backend, CHUNK_SHADERS, self.state, pass);
+		backend.set_viewport(commands, viewport_offset, viewport_extent);
+		backend.bind_texture(commands, &pipeline, &self.atlas);
+		backend.push_constants(commands, &pipeline, view_projection.to_cols_array_2d());
+		for chunk in self.chunks.values() {
+			backend.draw_indexed(
+				commands,
+				&pipeline,
+				&chunk.vertices,
+				&chunk.indices,
+				chunk.index_count,
+			);
+		}
 	}
 }
 
@@ -280,10 +282,28 @@ mod vs {
 		ty: "vertex",
 		src: r#"
 #version 460
-layout (location = 0) in vec2 position;
+layout (location = 0) in vec3 position;
+layout (location = 1) in vec3 normal;
+layout (location = 2) in vec2 uv;
+layout (location = 3) in uint tile;
+
+layout (location = 0) out float v_light;
+layout (location = 1) out vec2 v_uv;
+layout (location = 2) flat out vec2 v_tile_origin;
+
+// Must match texture::ATLAS_TILES
+const uint ATLAS_TILES = 16;
+
+layout (push_constant) uniform PushConstants {
+    mat4 view_projection;
+} pc;
 
 void main() {
-    gl_Position = vec4(position, 0.0, 1.0);
+    gl_Position = pc.view_projection * vec4(position, 1.0);
+
+    v_light = 0.6 + 0.4 * max(dot(normal, normalize(vec3(0.3, 1.0, 0.5))), 0.0);
+    v_uv = uv;
+    v_tile_origin = vec2(tile % ATLAS_TILES, tile / ATLAS_TILES) / float(ATLAS_TILES);
 }
 "#
 	}
@@ -294,10 +314,25 @@ mod fs {
 		ty: "fragment",
 		src: r#"
 #version 460
+layout (location = 0) in float v_light;
+layout (location = 1) in vec2 v_uv;
+layout (location = 2) flat in vec2 v_tile_origin;
+
 layout (location = 0) out vec4 f_color;
 
+layout (set = 0, binding = 0) uniform sampler2D atlas;
+
+// Must match texture::ATLAS_TILES
+const float ATLAS_TILES = 16.0;
+
 void main() {
-    f_color = vec4(1.0, 0.0, 0.0, 1.0);
+    // Merged faces span several blocks, repeat the tile across each of them
+    vec4 color = texture(atlas, v_tile_origin + fract(v_uv) / ATLAS_TILES);
+    // Cut out mostly transparent texels, like the gaps between leaves
+    if (color.a < 0.5) {
+        discard;
+    }
+    f_color = vec4(color.rgb * v_light, color.a);
 }
 "#
 	}
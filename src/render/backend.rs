@@ -0,0 +1,710 @@
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex},
+};
+
+use vulkano::{
+	buffer::{Buffer, BufferCreateInfo, Subbuffer},
+	command_buffer::{
+		allocator::StandardCommandBufferAllocator, AutoCommandBufferBuilder, CommandBufferUsage,
+		CopyBufferToImageInfo, CopyImageToBufferInfo, PrimaryAutoCommandBuffer,
+		RenderPassBeginInfo, RenderingAttachmentInfo, RenderingInfo, SubpassBeginInfo,
+		SubpassContents,
+	},
+	descriptor_set::{
+		allocator::StandardDescriptorSetAllocator, PersistentDescriptorSet, WriteDescriptorSet,
+	},
+	device::{DeviceOwned, Queue},
+	format::Format,
+	image::{
+		sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo},
+		view::ImageView,
+		Image, ImageCreateInfo, ImageType, ImageUsage,
+	},
+	memory::allocator::{AllocationCreateInfo, MemoryTypeFilter, StandardMemoryAllocator},
+	pipeline::{
+		graphics::{
+			color_blend::{AttachmentBlend, ColorBlendAttachmentState, ColorBlendState},
+			depth_stencil::{CompareOp, DepthState, DepthStencilState},
+			input_assembly::InputAssemblyState,
+			multisample::MultisampleState,
+			rasterization::{PolygonMode, RasterizationState},
+			subpass::{PipelineRenderingCreateInfo, PipelineSubpassType},
+			vertex_input::{
+				VertexInputAttributeDescription, VertexInputBindingDescription, VertexInputRate,
+				VertexInputState,
+			},
+			viewport::{Viewport, ViewportState},
+			GraphicsPipelineCreateInfo,
+		},
+		layout::PipelineDescriptorSetLayoutCreateInfo,
+		DynamicState, GraphicsPipeline, Pipeline as _, PipelineBindPoint, PipelineLayout,
+		PipelineShaderStageCreateInfo,
+	},
+	render_pass::{
+		AttachmentLoadOp, AttachmentStoreOp, Framebuffer, FramebufferCreateInfo, Subpass,
+	},
+	shader::EntryPoint,
+	sync::{self, GpuFuture},
+};
+
+use crate::logging::target;
+
+/// What a buffer is going to be bound as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BufferUsage {
+	Vertex,
+	Index,
+}
+
+/// Format of a single vertex attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AttributeFormat {
+	Float2,
+	Float3,
+	Uint,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VertexAttribute {
+	pub location: u32,
+	pub format: AttributeFormat,
+	pub offset: u32,
+}
+
+/// Layout of the single per-vertex buffer a pipeline reads from.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VertexLayout {
+	pub stride: u32,
+	pub attributes: Vec<VertexAttribute>,
+}
+
+/// Fixed-function state that varies between otherwise identical pipelines.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RenderState {
+	/// Rasterise polygon edges only.
+	pub wireframe: bool,
+	/// Alpha blend over the target instead of replacing it.
+	pub transparent: bool,
+}
+
+/// Identifies a pass for as long as it is alive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PassId(pub usize, pub u32);
+
+pub struct PipelineDesc<'a, B: Backend + ?Sized> {
+	pub vertex_shader: &'a B::Shader,
+	pub fragment_shader: &'a B::Shader,
+	pub vertex_layout: VertexLayout,
+	pub state: RenderState,
+	pub pass: &'a B::Pass,
+}
+
+/// The GPU operations the voxel renderer is written against. Everything
+/// outside of this module should only talk to the GPU through this trait so
+/// another graphics API can be slotted in later.
+pub trait Backend {
+	type Format: Copy;
+	type Buffer: Clone;
+	type Image: Clone;
+	/// An image sampled from shaders, along with how to sample it.
+	type Texture: Clone;
+	type Shader;
+	type Pipeline: Clone;
+	type Pass: Clone;
+	/// Commands being recorded inside of a pass.
+	type Commands;
+	/// A finished recording, ready to be submitted.
+	type CommandList;
+
+	/// Create a buffer initialised with `data`.
+	fn create_buffer(&self, usage: BufferUsage, data: &[u8]) -> Self::Buffer;
+	/// Create a texture from tightly packed sRGB RGBA8 `texels`.
+	fn create_texture(&self, extent: [u32; 2], texels: &[u8]) -> Self::Texture;
+	/// Create a pass with a single colour attachment of `color_format` and a
+	/// depth attachment.
+	fn create_pass(&self, color_format: Self::Format) -> Self::Pass;
+	fn pass_id(&self, pass: &Self::Pass) -> PassId;
+	fn create_pipeline(&self, desc: PipelineDesc<'_, Self>) -> Self::Pipeline;
+
+	/// Begin recording into `pass`, clearing `target` to `clear_color` and
+	/// `depth` to the far plane. `depth` must be the same size as `target`
+	/// and can't be used by another pass until this one has executed.
+	fn begin_pass(
+		&self,
+		pass: &Self::Pass,
+		target: Self::Image,
+		depth: Self::Image,
+		clear_color: [f32; 4],
+	) -> Self::Commands;
+	fn end_pass(&self, commands: Self::Commands) -> Self::CommandList;
+	/// Submit `commands` on their own and block until they have executed.
+	fn submit_and_wait(&self, commands: Self::CommandList);
+
+	/// Create a depth image for passes rendering to targets of `extent`.
+	fn create_depth_target(&self, extent: [u32; 2]) -> Self::Image;
+	/// Create an image that can be rendered to and then read back with
+	/// `read_image`.
+	fn create_render_target(&self, format: Self::Format, extent: [u32; 2]) -> Self::Image;
+	/// Copy the texels of `image` to host memory, blocking until the copy has
+	/// finished.
+	fn read_image(&self, image: &Self::Image) -> Vec<u8>;
+
+	fn set_viewport(&self, commands: &mut Self::Commands, offset: [f32; 2], extent: [f32; 2]);
+	/// Bind `texture` to set 0, binding 0 of `pipeline`, for draws that follow.
+	fn bind_texture(
+		&self,
+		commands: &mut Self::Commands,
+		pipeline: &Self::Pipeline,
+		texture: &Self::Texture,
+	);
+	/// Set the push constants of `pipeline` to `data`, for draws that follow.
+	fn push_constants<T: bytemuck::Pod + Send + Sync>(
+		&self,
+		commands: &mut Self::Commands,
+		pipeline: &Self::Pipeline,
+		data: T,
+	);
+	/// Draw `index_count` indices from `indices`, a buffer of `u32`s.
+	fn draw_indexed(
+		&self,
+		commands: &mut Self::Commands,
+		pipeline: &Self::Pipeline,
+		vertices: &Self::Buffer,
+		indices: &Self::Buffer,
+		index_count: u32,
+	);
+}
+
+/// Format of the depth attachment every pass has.
+const DEPTH_FORMAT: Format = Format::D32_SFLOAT;
+
+pub struct VulkanoBackend {
+	allocator: Arc<StandardMemoryAllocator>,
+	gfx_queue: Arc<Queue>,
+	command_buffer_allocator: StandardCommandBufferAllocator,
+	descriptor_set_allocator: StandardDescriptorSetAllocator,
+	/// Descriptor sets binding a texture to a pipeline layout, keyed by the
+	/// layout and image view. The layout is kept alive alongside its set so
+	/// that neither address can be reused while the entry exists.
+	texture_sets: Mutex<TextureSets>,
+}
+
+type TextureSets = HashMap<(usize, usize), (Arc<PipelineLayout>, Arc<PersistentDescriptorSet>)>;
+
+impl VulkanoBackend {
+	pub fn new(allocator: Arc<StandardMemoryAllocator>, gfx_queue: Arc<Queue>) -> Self {
+		let command_buffer_allocator =
+			StandardCommandBufferAllocator::new(allocator.device().clone(), Default::default());
+		let descriptor_set_allocator =
+			StandardDescriptorSetAllocator::new(allocator.device().clone(), Default::default());
+
+		Self {
+			allocator,
+			gfx_queue,
+			command_buffer_allocator,
+			descriptor_set_allocator,
+			texture_sets: Mutex::new(HashMap::new()),
+		}
+	}
+}
+
+/// A pass on the Vulkano backend. Dynamic rendering is used when the device
+/// has it enabled, avoiding render pass and framebuffer objects entirely,
+/// otherwise this falls back to a classic render pass.
+#[derive(Clone)]
+pub enum VulkanoPass {
+	RenderPass(Subpass),
+	Dynamic { color_format: Format },
+}
+
+impl VulkanoPass {
+	fn subpass_type(&self) -> PipelineSubpassType {
+		match self {
+			VulkanoPass::RenderPass(subpass) => subpass.clone().into(),
+			VulkanoPass::Dynamic { color_format } => PipelineRenderingCreateInfo {
+				color_attachment_formats: vec![Some(*color_format)],
+				depth_attachment_format: Some(DEPTH_FORMAT),
+				..Default::default()
+			}
+			.into(),
+		}
+	}
+
+	fn num_color_attachments(&self) -> u32 {
+		match self {
+			VulkanoPass::RenderPass(subpass) => subpass.num_color_attachments(),
+			VulkanoPass::Dynamic { .. } => 1,
+		}
+	}
+}
+
+#[derive(Clone)]
+pub struct VulkanoTexture {
+	view: Arc<ImageView>,
+	sampler: Arc<Sampler>,
+}
+
+pub struct VulkanoCommands {
+	builder: AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+	dynamic: bool,
+}
+
+impl AttributeFormat {
+	fn to_vulkano(self) -> Format {
+		match self {
+			AttributeFormat::Float2 => Format::R32G32_SFLOAT,
+			AttributeFormat::Float3 => Format::R32G32B32_SFLOAT,
+			AttributeFormat::Uint => Format::R32_UINT,
+		}
+	}
+}
+
+impl Backend for VulkanoBackend {
+	type Format = Format;
+	type Buffer = Subbuffer<[u8]>;
+	type Image = Arc<ImageView>;
+	type Texture = VulkanoTexture;
+	type Shader = EntryPoint;
+	type Pipeline = Arc<GraphicsPipeline>;
+	type Pass = VulkanoPass;
+	type Commands = VulkanoCommands;
+	type CommandList = Arc<PrimaryAutoCommandBuffer>;
+
+	fn create_buffer(&self, usage: BufferUsage, data: &[u8]) -> Self::Buffer {
+		let usage = match usage {
+			BufferUsage::Vertex => vulkano::buffer::BufferUsage::VERTEX_BUFFER,
+			BufferUsage::Index => vulkano::buffer::BufferUsage::INDEX_BUFFER,
+		};
+		Buffer::from_iter(
+			self.allocator.clone(),
+			BufferCreateInfo {
+				usage,
+				..Default::default()
+			},
+			AllocationCreateInfo {
+				memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
+					| MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+				..Default::default()
+			},
+			data.iter().copied(),
+		)
+		.unwrap()
+	}
+
+	fn create_texture(&self, extent: [u32; 2], texels: &[u8]) -> VulkanoTexture {
+		let staging = Buffer::from_iter(
+			self.allocator.clone(),
+			BufferCreateInfo {
+				usage: vulkano::buffer::BufferUsage::TRANSFER_SRC,
+				..Default::default()
+			},
+			AllocationCreateInfo {
+				memory_type_filter: MemoryTypeFilter::PREFER_HOST
+					| MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+				..Default::default()
+			},
+			texels.iter().copied(),
+		)
+		.unwrap();
+		let image = Image::new(
+			self.allocator.clone(),
+			ImageCreateInfo {
+				image_type: ImageType::Dim2d,
+				format: Format::R8G8B8A8_SRGB,
+				extent: [extent[0], extent[1], 1],
+				usage: ImageUsage::TRANSFER_DST | ImageUsage::SAMPLED,
+				..Default::default()
+			},
+			AllocationCreateInfo::default(),
+		)
+		.unwrap();
+
+		let mut builder = AutoCommandBufferBuilder::primary(
+			&self.command_buffer_allocator,
+			self.gfx_queue.queue_family_index(),
+			CommandBufferUsage::OneTimeSubmit,
+		)
+		.unwrap();
+		builder
+			.copy_buffer_to_image(CopyBufferToImageInfo::buffer_image(staging, image.clone()))
+			.unwrap();
+		self.submit_and_wait(builder.build().unwrap());
+
+		// Nearest filtering keeps block textures crisp up close
+		let sampler = Sampler::new(
+			self.allocator.device().clone(),
+			SamplerCreateInfo {
+				mag_filter: Filter::Nearest,
+				min_filter: Filter::Nearest,
+				address_mode: [SamplerAddressMode::ClampToEdge; 3],
+				..Default::default()
+			},
+		)
+		.unwrap();
+		VulkanoTexture {
+			view: ImageView::new_default(image).unwrap(),
+			sampler,
+		}
+	}
+
+	fn create_pass(&self, color_format: Format) -> VulkanoPass {
+		let device = self.gfx_queue.device();
+		if device.enabled_features().dynamic_rendering {
+			return VulkanoPass::Dynamic { color_format };
+		}
+
+		let render_pass = vulkano::single_pass_renderpass!(device.clone(),
+			attachments: {
+				color: {
+					format: color_format,
+					samples: 1,
+					load_op: Clear,
+					store_op: Store,
+				},
+				depth: {
+					format: DEPTH_FORMAT,
+					samples: 1,
+					load_op: Clear,
+					store_op: DontCare,
+				}
+			},
+			pass: {
+					color: [color],
+					depth_stencil: {depth}
+			}
+		)
+		.unwrap();
+		VulkanoPass::RenderPass(Subpass::from(render_pass, 0).unwrap())
+	}
+
+	fn pass_id(&self, pass: &VulkanoPass) -> PassId {
+		match pass {
+			VulkanoPass::RenderPass(subpass) => {
+				PassId(Arc::as_ptr(subpass.render_pass()) as usize, subpass.index())
+			}
+			// Dynamic passes are fully described by their attachment formats
+			VulkanoPass::Dynamic { color_format } => PassId(0, *color_format as u32),
+		}
+	}
+
+	fn create_pipeline(&self, desc: PipelineDesc<'_, Self>) -> Self::Pipeline {
+		let device = self.allocator.device().clone();
+		let polygon_mode = if !desc.state.wireframe {
+			PolygonMode::Fill
+		} else if device.enabled_features().fill_mode_non_solid {
+			PolygonMode::Line
+		} else {
+			bevy::log::warn!(
+				target: target::RENDER,
+				"Wireframe requested but fill_mode_non_solid is not enabled"
+			);
+			PolygonMode::Fill
+		};
+		let blend = desc.state.transparent.then(AttachmentBlend::alpha);
+		// Transparent surfaces are tested against depth but don't hide what
+		// is drawn behind them afterwards
+		let depth = DepthState {
+			write_enable: !desc.state.transparent,
+			compare_op: CompareOp::Less,
+		};
+		let vertex_input_state = desc.vertex_layout.attributes.iter().fold(
+			VertexInputState::new().binding(
+				0,
+				VertexInputBindingDescription {
+					stride: desc.vertex_layout.stride,
+					input_rate: VertexInputRate::Vertex,
+				},
+			),
+			|state, attribute| {
+				state.attribute(
+					attribute.location,
+					VertexInputAttributeDescription {
+						binding: 0,
+						format: attribute.format.to_vulkano(),
+						offset: attribute.offset,
+					},
+				)
+			},
+		);
+		let stages = [
+			PipelineShaderStageCreateInfo::new(desc.vertex_shader.clone()),
+			PipelineShaderStageCreateInfo::new(desc.fragment_shader.clone()),
+		];
+		let layout = PipelineLayout::new(
+			device.clone(),
+			PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
+				.into_pipeline_layout_create_info(device.clone())
+				.unwrap(),
+		)
+		.unwrap();
+
+		GraphicsPipeline::new(
+			device,
+			None,
+			GraphicsPipelineCreateInfo {
+				stages: stages.into_iter().collect(),
+				vertex_input_state: Some(vertex_input_state),
+				input_assembly_state: Some(InputAssemblyState::default()),
+				viewport_state: Some(ViewportState::default()),
+				rasterization_state: Some(RasterizationState {
+					polygon_mode,
+					..Default::default()
+				}),
+				multisample_state: Some(MultisampleState::default()),
+				depth_stencil_state: Some(DepthStencilState {
+					depth: Some(depth),
+					..Default::default()
+				}),
+				color_blend_state: Some(ColorBlendState::with_attachment_states(
+					desc.pass.num_color_attachments(),
+					ColorBlendAttachmentState {
+						blend,
+						..Default::default()
+					},
+				)),
+				dynamic_state: [DynamicState::Viewport].into_iter().collect(),
+				subpass: Some(desc.pass.subpass_type()),
+				..GraphicsPipelineCreateInfo::layout(layout)
+			},
+		)
+		.unwrap()
+	}
+
+	fn begin_pass(
+		&self,
+		pass: &VulkanoPass,
+		target: Arc<ImageView>,
+		depth: Arc<ImageView>,
+		clear_color: [f32; 4],
+	) -> Self::Commands {
+		let mut builder = AutoCommandBufferBuilder::primary(
+			&self.command_buffer_allocator,
+			self.gfx_queue.queue_family_index(),
+			CommandBufferUsage::OneTimeSubmit,
+		)
+		.unwrap();
+		match pass {
+			VulkanoPass::RenderPass(subpass) => {
+				let framebuffer = Framebuffer::new(
+					subpass.render_pass().clone(),
+					FramebufferCreateInfo {
+						attachments: vec![target, depth],
+						..Default::default()
+					},
+				)
+				.unwrap();
+				builder
+					.begin_render_pass(
+						RenderPassBeginInfo {
+							clear_values: vec![Some(clear_color.into()), Some(1.0.into())],
+							..RenderPassBeginInfo::framebuffer(framebuffer)
+						},
+						SubpassBeginInfo {
+							contents: SubpassContents::Inline,
+							..Default::default()
+						},
+					)
+					.unwrap();
+			}
+			VulkanoPass::Dynamic { .. } => {
+				builder
+					.begin_rendering(RenderingInfo {
+						color_attachments: vec![Some(RenderingAttachmentInfo {
+							load_op: AttachmentLoadOp::Clear,
+							store_op: AttachmentStoreOp::Store,
+							clear_value: Some(clear_color.into()),
+							..RenderingAttachmentInfo::image_view(target)
+						})],
+						depth_attachment: Some(RenderingAttachmentInfo {
+							load_op: AttachmentLoadOp::Clear,
+							store_op: AttachmentStoreOp::DontCare,
+							clear_value: Some(1.0.into()),
+							..RenderingAttachmentInfo::image_view(depth)
+						}),
+						..Default::default()
+					})
+					.unwrap();
+			}
+		}
+
+		VulkanoCommands {
+			builder,
+			dynamic: matches!(pass, VulkanoPass::Dynamic { .. }),
+		}
+	}
+
+	fn end_pass(&self, mut commands: Self::Commands) -> Self::CommandList {
+		if commands.dynamic {
+			commands.builder.end_rendering().unwrap();
+		} else {
+			commands
+				.builder
+				.end_render_pass(Default::default())
+				.unwrap();
+		}
+		commands.builder.build().unwrap()
+	}
+
+	fn submit_and_wait(&self, commands: Self::CommandList) {
+		sync::now(self.gfx_queue.device().clone())
+			.then_execute(self.gfx_queue.clone(), commands)
+			.unwrap()
+			.then_signal_fence_and_flush()
+			.unwrap()
+			.wait(None)
+			.unwrap();
+	}
+
+	fn create_depth_target(&self, extent: [u32; 2]) -> Arc<ImageView> {
+		let image = Image::new(
+			self.allocator.clone(),
+			ImageCreateInfo {
+				image_type: ImageType::Dim2d,
+				format: DEPTH_FORMAT,
+				extent: [extent[0], extent[1], 1],
+				usage: ImageUsage::DEPTH_STENCIL_ATTACHMENT,
+				..Default::default()
+			},
+			AllocationCreateInfo::default(),
+		)
+		.unwrap();
+		ImageView::new_default(image).unwrap()
+	}
+
+	fn create_render_target(&self, format: Format, extent: [u32; 2]) -> Arc<ImageView> {
+		let image = Image::new(
+			self.allocator.clone(),
+			ImageCreateInfo {
+				image_type: ImageType::Dim2d,
+				format,
+				extent: [extent[0], extent[1], 1],
+				usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSFER_SRC,
+				..Default::default()
+			},
+			AllocationCreateInfo::default(),
+		)
+		.unwrap();
+		ImageView::new_default(image).unwrap()
+	}
+
+	fn read_image(&self, image: &Arc<ImageView>) -> Vec<u8> {
+		let image = image.image().clone();
+		let [width, height, depth] = image.extent();
+		let size = width as u64 * height as u64 * depth as u64 * image.format().block_size();
+		let buffer = Buffer::new_slice::<u8>(
+			self.allocator.clone(),
+			BufferCreateInfo {
+				usage: vulkano::buffer::BufferUsage::TRANSFER_DST,
+				..Default::default()
+			},
+			AllocationCreateInfo {
+				memory_type_filter: MemoryTypeFilter::PREFER_HOST
+					| MemoryTypeFilter::HOST_RANDOM_ACCESS,
+				..Default::default()
+			},
+			size,
+		)
+		.unwrap();
+
+		let mut builder = AutoCommandBufferBuilder::primary(
+			&self.command_buffer_allocator,
+			self.gfx_queue.queue_family_index(),
+			CommandBufferUsage::OneTimeSubmit,
+		)
+		.unwrap();
+		builder
+			.copy_image_to_buffer(CopyImageToBufferInfo::image_buffer(image, buffer.clone()))
+			.unwrap();
+		self.submit_and_wait(builder.build().unwrap());
+
+		let texels = buffer.read().unwrap().to_vec();
+		texels
+	}
+
+	fn set_viewport(&self, commands: &mut Self::Commands, offset: [f32; 2], extent: [f32; 2]) {
+		commands
+			.builder
+			.set_viewport(
+				0,
+				[Viewport {
+					offset,
+					extent,
+					depth_range: 0.0..=1.0,
+				}]
+				.into_iter()
+				.collect(),
+			)
+			.unwrap();
+	}
+
+	fn bind_texture(
+		&self,
+		commands: &mut Self::Commands,
+		pipeline: &Self::Pipeline,
+		texture: &VulkanoTexture,
+	) {
+		let layout = pipeline.layout();
+		let key = (
+			Arc::as_ptr(layout) as usize,
+			Arc::as_ptr(&texture.view) as usize,
+		);
+		let set = self
+			.texture_sets
+			.lock()
+			.unwrap()
+			.entry(key)
+			.or_insert_with(|| {
+				let set = PersistentDescriptorSet::new(
+					&self.descriptor_set_allocator,
+					layout.set_layouts()[0].clone(),
+					[WriteDescriptorSet::image_view_sampler(
+						0,
+						texture.view.clone(),
+						texture.sampler.clone(),
+					)],
+					[],
+				)
+				.unwrap();
+				(layout.clone(), set)
+			})
+			.1
+			.clone();
+		commands
+			.builder
+			.bind_descriptor_sets(PipelineBindPoint::Graphics, layout.clone(), 0, set)
+			.unwrap();
+	}
+
+	fn push_constants<T: bytemuck::Pod + Send + Sync>(
+		&self,
+		commands: &mut Self::Commands,
+		pipeline: &Self::Pipeline,
+		data: T,
+	) {
+		commands
+			.builder
+			.push_constants(pipeline.layout().clone(), 0, data)
+			.unwrap();
+	}
+
+	fn draw_indexed(
+		&self,
+		commands: &mut Self::Commands,
+		pipeline: &Self::Pipeline,
+		vertices: &Self::Buffer,
+		indices: &Self::Buffer,
+		index_count: u32,
+	) {
+		commands
+			.builder
+			.bind_pipeline_graphics(pipeline.clone())
+			.unwrap()
+			.bind_vertex_buffers(0, vertices.clone())
+			.unwrap()
+			.bind_index_buffer(indices.clone().reinterpret::<[u32]>())
+			.unwrap()
+			.draw_indexed(index_count, 1, 0, 0, 0)
+			.unwrap();
+	}
+}
@@ -0,0 +1,90 @@
+use bevy::utils::HashMap;
+
+use super::backend::{Backend, PassId, PipelineDesc, RenderState, VertexLayout};
+
+/// Name a set of shaders is registered under.
+pub type ShaderSetId = &'static str;
+
+struct ShaderSet<B: Backend> {
+	vertex_shader: B::Shader,
+	fragment_shader: B::Shader,
+	vertex_layout: VertexLayout,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct VariantKey {
+	shaders: ShaderSetId,
+	state: RenderState,
+	pass: PassId,
+}
+
+/// Creates pipelines on demand from registered shader sets, sharing a single
+/// pipeline between every user of the same shaders, render state and pass.
+pub struct PipelineCache<B: Backend> {
+	shader_sets: HashMap<ShaderSetId, ShaderSet<B>>,
+	// The pass is kept alive alongside its pipelines so its id can't be reused
+	variants: HashMap<VariantKey, (B::Pass, B::Pipeline)>,
+}
+
+impl<B: Backend> Default for PipelineCache<B> {
+	fn default() -> Self {
+		Self {
+			shader_sets: HashMap::default(),
+			variants: HashMap::default(),
+		}
+	}
+}
+
+impl<B: Backend> PipelineCache<B> {
+	/// Register a shader set, or replace the shaders of an existing one. Any
+	/// variants built from the old shaders are rebuilt on next use.
+	pub fn register_shaders(
+		&mut self,
+		id: ShaderSetId,
+		vertex_shader: B::Shader,
+		fragment_shader: B::Shader,
+		vertex_layout: VertexLayout,
+	) {
+		let set = ShaderSet {
+			vertex_shader,
+			fragment_shader,
+			vertex_layout,
+		};
+		if self.shader_sets.insert(id, set).is_some() {
+			self.variants.retain(|key, _| key.shaders != id);
+		}
+	}
+
+	/// Get the pipeline for the shader set `id` with `state` in `pass`,
+	/// creating it if this variant hasn't been used before.
+	///
+	/// Panics if no shader set is registered as `id`.
+	pub fn get(
+		&mut self,
+		backend: &B,
+		id: ShaderSetId,
+		state: RenderState,
+		pass: &B::Pass,
+	) -> B::Pipeline {
+		let key = VariantKey {
+			shaders: id,
+			state,
+			pass: backend.pass_id(pass),
+		};
+		let shader_sets = &self.shader_sets;
+		let (_, pipeline) = self.variants.entry(key).or_insert_with(|| {
+			let set = shader_sets
+				.get(id)
+				.unwrap_or_else(|| panic!("shader set {:?} is not registered", id));
+			let pipeline = backend.create_pipeline(PipelineDesc {
+				vertex_shader: &set.vertex_shader,
+				fragment_shader: &set.fragment_shader,
+				vertex_layout: set.vertex_layout.clone(),
+				state,
+				pass,
+			});
+			(pass.clone(), pipeline)
+		});
+		pipeline.clone()
+	}
+}
@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+
+/// The stages the engine runs each frame, in order. Plugins can order their
+/// own systems relative to these.
+///
+/// All but `Render` run in `Update`, `Render` runs in `PostUpdate` so that it
+/// sees every change made during the frame.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EngineSet {
+	/// Read devices and the UI, turning them into changes to settings and
+	/// intents for the simulation.
+	Input,
+	/// Advance gameplay state.
+	Simulation,
+	/// Apply changes to the voxel world.
+	WorldUpdate,
+	/// Prepare geometry for anything that changed.
+	MeshPrep,
+	/// Draw and present every window.
+	Render,
+}
+
+pub struct EngineSchedulePlugin;
+
+impl Plugin for EngineSchedulePlugin {
+	fn build(&self, app: &mut App) {
+		app.configure_sets(
+			Update,
+			(
+				EngineSet::Input,
+				EngineSet::Simulation,
+				EngineSet::WorldUpdate,
+				EngineSet::MeshPrep,
+			)
+				.chain(),
+		)
+		.configure_sets(PostUpdate, EngineSet::Render);
+	}
+}
@@ -0,0 +1,203 @@
+use bevy::{
+	prelude::*,
+	window::{PresentMode, PrimaryWindow, WindowMode},
+};
+use bevy_vulkano::{egui_winit_vulkano::egui, BevyVulkanoWindows};
+
+/// Graphics options that can be changed at runtime from the settings menu.
+#[derive(Resource, Clone, PartialEq)]
+pub struct GraphicsSettings {
+	pub resolution: (f32, f32),
+	pub present_mode: PresentMode,
+	pub mode: WindowMode,
+	/// Frame rate cap, independent of the present mode.
+	pub fps_limit: Option<u32>,
+	/// Frame rate cap while the window is unfocused, to save power.
+	pub unfocused_fps_limit: Option<u32>,
+}
+
+impl Default for GraphicsSettings {
+	fn default() -> Self {
+		Self {
+			resolution: (1920.0, 1080.0),
+			present_mode: PresentMode::Fifo,
+			mode: WindowMode::Windowed,
+			fps_limit: None,
+			unfocused_fps_limit: Some(30),
+		}
+	}
+}
+
+impl GraphicsSettings {
+	pub fn window(&self) -> Window {
+		Window {
+			resolution: self.resolution.into(),
+			present_mode: self.present_mode,
+			resizable: true,
+			mode: self.mode,
+			..default()
+		}
+	}
+
+	fn apply_to(&self, window: &mut Window) {
+		window.resolution.set(self.resolution.0, self.resolution.1);
+		window.present_mode = self.present_mode;
+		window.mode = self.mode;
+	}
+}
+
+/// Tuning for the free-fly camera controller.
+#[derive(Resource, Clone, PartialEq)]
+pub struct ControlSettings {
+	/// Radians turned per pixel of mouse movement.
+	pub mouse_sensitivity: f32,
+	/// Flying speed in blocks per second.
+	pub fly_speed: f32,
+	/// Speed multiplier while shift is held.
+	pub boost_multiplier: f32,
+}
+
+impl Default for ControlSettings {
+	fn default() -> Self {
+		Self {
+			mouse_sensitivity: 0.003,
+			fly_speed: 16.0,
+			boost_multiplier: 4.0,
+		}
+	}
+}
+
+/// State of the settings menu, `pending` holds edits that have not been
+/// applied yet.
+#[derive(Resource, Default)]
+pub struct SettingsMenu {
+	pub open: bool,
+	pending: Option<GraphicsSettings>,
+}
+
+pub fn toggle_settings_menu(keys: Res<Input<KeyCode>>, mut menu: ResMut<SettingsMenu>) {
+	if keys.just_pressed(KeyCode::F1) {
+		menu.open = !menu.open;
+		menu.pending = None;
+	}
+}
+
+pub fn settings_menu_ui(
+	mut window_query: Query<(Entity, &mut Window), With<PrimaryWindow>>,
+	vulkano_windows: NonSend<BevyVulkanoWindows>,
+	mut menu: ResMut<SettingsMenu>,
+	mut settings: ResMut<GraphicsSettings>,
+) {
+	if !menu.open {
+		return;
+	}
+	let Ok((window_entity, mut window)) = window_query.get_single_mut() else {
+		return;
+	};
+	let Some(vulkano_window) = vulkano_windows.get_vulkano_window(window_entity) else {
+		return;
+	};
+	let ctx = vulkano_window.gui.context();
+
+	let mut apply = false;
+	let mut revert = false;
+	let pending = menu.pending.get_or_insert_with(|| settings.clone());
+	egui::Window::new("Settings")
+		.resizable(false)
+		.collapsible(false)
+		.show(&ctx, |ui| {
+			ui.heading("Graphics");
+			egui::Grid::new("graphics").num_columns(2).show(ui, |ui| {
+				ui.label("Resolution");
+				ui.horizontal(|ui| {
+					ui.add(
+						egui::DragValue::new(&mut pending.resolution.0).clamp_range(640.0..=7680.0),
+					);
+					ui.label("x");
+					ui.add(
+						egui::DragValue::new(&mut pending.resolution.1).clamp_range(480.0..=4320.0),
+					);
+				});
+				ui.end_row();
+
+				ui.label("Present mode");
+				egui::ComboBox::from_id_source("present_mode")
+					.selected_text(format!("{:?}", pending.present_mode))
+					.show_ui(ui, |ui| {
+						for mode in [
+							PresentMode::Fifo,
+							PresentMode::FifoRelaxed,
+							PresentMode::Mailbox,
+							PresentMode::Immediate,
+						] {
+							ui.selectable_value(
+								&mut pending.present_mode,
+								mode,
+								format!("{:?}", mode),
+							);
+						}
+					});
+				ui.end_row();
+
+				ui.label("Window mode");
+				egui::ComboBox::from_id_source("window_mode")
+					.selected_text(format!("{:?}", pending.mode))
+					.show_ui(ui, |ui| {
+						for mode in [
+							WindowMode::Windowed,
+							WindowMode::BorderlessFullscreen,
+							WindowMode::Fullscreen,
+						] {
+							ui.selectable_value(&mut pending.mode, mode, format!("{:?}", mode));
+						}
+					});
+				ui.end_row();
+
+				ui.label("Frame limit");
+				fps_limit_edit(ui, &mut pending.fps_limit, 144);
+				ui.end_row();
+
+				ui.label("Unfocused frame limit");
+				fps_limit_edit(ui, &mut pending.unfocused_fps_limit, 30);
+				ui.end_row();
+			});
+
+			ui.separator();
+			ui.horizontal(|ui| {
+				let changed = *pending != *settings;
+				apply = ui
+					.add_enabled(changed, egui::Button::new("Apply"))
+					.clicked();
+				revert = ui
+					.add_enabled(changed, egui::Button::new("Revert"))
+					.clicked();
+			});
+		});
+
+	if apply {
+		if let Some(pending) = menu.pending.clone() {
+			pending.apply_to(&mut window);
+			*settings = pending;
+		}
+	} else if revert {
+		menu.pending = None;
+	}
+}
+
+fn fps_limit_edit(ui: &mut egui::Ui, limit: &mut Option<u32>, default: u32) {
+	ui.horizontal(|ui| {
+		let mut enabled = limit.is_some();
+		ui.checkbox(&mut enabled, "");
+		match (enabled, limit.as_mut()) {
+			(true, Some(fps)) => {
+				ui.add(
+					egui::DragValue::new(fps)
+						.clamp_range(1..=1000)
+						.suffix(" fps"),
+				);
+			}
+			(true, None) => *limit = Some(default),
+			(false, _) => *limit = None,
+		}
+	});
+}
@@ -0,0 +1,53 @@
+use std::sync::{
+	atomic::{AtomicBool, Ordering},
+	Arc,
+};
+
+use bevy::{app::AppExit, prelude::*};
+use bevy_vulkano::BevyVulkanoContext;
+
+use crate::logging::target;
+
+/// Orders shutdown so that the app only exits once the GPU has finished with
+/// everything that is about to be dropped.
+pub struct ShutdownPlugin;
+
+impl Plugin for ShutdownPlugin {
+	fn build(&self, app: &mut App) {
+		let interrupted = Interrupted::default();
+		let flag = interrupted.0.clone();
+		if let Err(e) = ctrlc::set_handler(move || flag.store(true, Ordering::Relaxed)) {
+			bevy::log::warn!(target: target::APP, "Failed to install interrupt handler: {}", e);
+		}
+
+		app.insert_resource(interrupted)
+			.add_systems(First, exit_on_interrupt)
+			.add_systems(Last, wait_for_gpu_on_exit);
+	}
+}
+
+/// Set by the SIGINT handler.
+#[derive(Resource, Default)]
+struct Interrupted(Arc<AtomicBool>);
+
+fn exit_on_interrupt(interrupted: Res<Interrupted>, mut exit: EventWriter<AppExit>) {
+	if interrupted.0.swap(false, Ordering::Relaxed) {
+		bevy::log::info!(target: target::APP, "Interrupted, shutting down");
+		exit.send(AppExit);
+	}
+}
+
+fn wait_for_gpu_on_exit(mut exit: EventReader<AppExit>, context: Res<BevyVulkanoContext>) {
+	if exit.read().next().is_some() {
+		wait_for_gpu(&context);
+	}
+}
+
+/// Block until the device has finished all submitted work.
+pub fn wait_for_gpu(context: &BevyVulkanoContext) {
+	// Safety: queue submission only happens in the render system, which never
+	// runs concurrently with its callers.
+	if let Err(e) = unsafe { context.context.device().wait_idle() } {
+		bevy::log::error!(target: target::RENDER, "Failed to wait for device idle: {}", e);
+	}
+}
@@ -0,0 +1,75 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+
+use crate::logging::target;
+
+/// Where the block texture atlas is loaded from, relative to the working
+/// directory.
+pub const ATLAS_PATH: &str = "assets/textures/blocks.png";
+/// The atlas is a square grid with this many tiles along each edge, tile `n`
+/// is at column `n % ATLAS_TILES` and row `n / ATLAS_TILES`. The chunk shaders
+/// assume the same layout.
+pub const ATLAS_TILES: u32 = 16;
+
+/// Block textures packed into a single image, as sRGB RGBA8 texels.
+#[derive(Resource, Clone)]
+pub struct TextureAtlas {
+	extent: [u32; 2],
+	texels: Vec<u8>,
+}
+
+impl TextureAtlas {
+	pub fn load(path: impl AsRef<Path>) -> image::ImageResult<Self> {
+		let image = image::open(path)?.into_rgba8();
+		Ok(Self {
+			extent: [image.width(), image.height()],
+			texels: image.into_raw(),
+		})
+	}
+
+	/// Load the atlas at `path`, falling back to a checkerboard if it can't
+	/// be read so missing assets are obvious rather than fatal.
+	pub fn load_or_fallback(path: impl AsRef<Path>) -> Self {
+		let path = path.as_ref();
+		match Self::load(path) {
+			Ok(atlas) => atlas,
+			Err(e) => {
+				bevy::log::error!(
+					target: target::APP,
+					"Failed to load texture atlas {}: {}",
+					path.display(),
+					e
+				);
+				Self::fallback()
+			}
+		}
+	}
+
+	/// Magenta and black checkerboard in every tile.
+	pub fn fallback() -> Self {
+		let size = ATLAS_TILES * 2;
+		let texels = (0..size * size)
+			.flat_map(|i| {
+				let (x, y) = (i % size, i / size);
+				if (x + y) % 2 == 0 {
+					[255, 0, 255, 255]
+				} else {
+					[0, 0, 0, 255]
+				}
+			})
+			.collect();
+		Self {
+			extent: [size, size],
+			texels,
+		}
+	}
+
+	pub fn extent(&self) -> [u32; 2] {
+		self.extent
+	}
+
+	pub fn texels(&self) -> &[u8] {
+		&self.texels
+	}
+}
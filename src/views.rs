@@ -0,0 +1,87 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{camera::Camera, render::View};
+
+const MAX_SPLIT: usize = 4;
+
+/// The views a window is split into, drawn side by side in the same
+/// swapchain image. Every window gets its own.
+#[derive(Component)]
+pub struct Views(pub Vec<View>);
+
+impl Default for Views {
+	fn default() -> Self {
+		Self(vec![View::full(Camera::default())])
+	}
+}
+
+impl Views {
+	/// Split the target evenly between `count` views, side by side for two
+	/// and in quadrants for up to four. Existing views keep their cameras,
+	/// new ones start from the first view's camera turned a further quarter
+	/// turn each.
+	pub fn split(&self, count: usize) -> Self {
+		let count = count.clamp(1, MAX_SPLIT);
+		let first = self
+			.0
+			.first()
+			.map_or_else(Camera::default, |view| view.camera);
+		let camera = |i: usize| {
+			self.0.get(i).map_or_else(
+				|| Camera {
+					rotation: Quat::from_rotation_y(i as f32 * FRAC_PI_2) * first.rotation,
+					..first
+				},
+				|view| view.camera,
+			)
+		};
+		let views = match count {
+			1 => vec![View::full(camera(0))],
+			2 => vec![
+				View {
+					offset: [0.0, 0.0],
+					extent: [0.5, 1.0],
+					camera: camera(0),
+				},
+				View {
+					offset: [0.5, 0.0],
+					extent: [0.5, 1.0],
+					camera: camera(1),
+				},
+			],
+			_ => (0..count)
+				.map(|i| View {
+					offset: [(i % 2) as f32 * 0.5, (i / 2) as f32 * 0.5],
+					extent: [0.5, 0.5],
+					camera: camera(i),
+				})
+				.collect(),
+		};
+		Self(views)
+	}
+}
+
+/// Give new windows a single full view.
+pub fn add_window_views(
+	mut commands: Commands,
+	windows: Query<Entity, (With<Window>, Without<Views>)>,
+) {
+	for window in &windows {
+		commands.entity(window).insert(Views::default());
+	}
+}
+
+/// Cycle the primary window through one to four views.
+pub fn cycle_split_screen(
+	keys: Res<Input<KeyCode>>,
+	mut views: Query<&mut Views, With<PrimaryWindow>>,
+) {
+	if !keys.just_pressed(KeyCode::F4) {
+		return;
+	}
+	if let Ok(mut views) = views.get_single_mut() {
+		*views = views.split(views.0.len() % MAX_SPLIT + 1);
+	}
+}
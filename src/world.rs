@@ -0,0 +1,189 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{blocks::BlockRegistry, logging::target};
+
+/// Length of a chunk edge in blocks.
+pub const CHUNK_SIZE: usize = 32;
+pub const CHUNK_VOLUME: usize = CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE;
+
+/// Identifies a type of block. `BlockId::AIR` is empty space.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BlockId(pub u16);
+
+impl BlockId {
+	pub const AIR: BlockId = BlockId(0);
+
+	pub fn is_air(self) -> bool {
+		self == Self::AIR
+	}
+}
+
+static NEXT_VERSION: AtomicU64 = AtomicU64::new(1);
+
+/// Versions are drawn from one process-wide counter, so a version is never
+/// reused even when a chunk is replaced.
+fn next_version() -> u64 {
+	NEXT_VERSION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Counters bumped whenever part of a chunk changes. Systems that derive data
+/// from a chunk remember the version they last saw, if the chunk's version
+/// has moved on since then their copy is stale.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChunkVersions {
+	/// Blocks changed.
+	pub data: u64,
+	/// Lighting changed.
+	// Nothing reads this until there is a lighting pass
+	#[allow(dead_code)]
+	pub light: u64,
+	/// A new mesh was built.
+	pub mesh: u64,
+}
+
+impl ChunkVersions {
+	fn new() -> Self {
+		let version = next_version();
+		Self {
+			data: version,
+			light: version,
+			mesh: version,
+		}
+	}
+}
+
+/// A cube of `CHUNK_SIZE`³ blocks, stored x-major then z then y.
+#[derive(Clone)]
+pub struct Chunk {
+	blocks: Box<[BlockId; CHUNK_VOLUME]>,
+	versions: ChunkVersions,
+}
+
+impl Default for Chunk {
+	fn default() -> Self {
+		Self {
+			blocks: Box::new([BlockId::AIR; CHUNK_VOLUME]),
+			versions: ChunkVersions::new(),
+		}
+	}
+}
+
+impl Chunk {
+	fn index(pos: UVec3) -> usize {
+		debug_assert!(pos.cmplt(UVec3::splat(CHUNK_SIZE as u32)).all());
+		pos.x as usize + (pos.z as usize + pos.y as usize * CHUNK_SIZE) * CHUNK_SIZE
+	}
+
+	/// Get the block at `pos`, relative to the chunk's origin.
+	pub fn get(&self, pos: UVec3) -> BlockId {
+		self.blocks[Self::index(pos)]
+	}
+
+	/// Set the block at `pos`, relative to the chunk's origin.
+	pub fn set(&mut self, pos: UVec3, block: BlockId) {
+		let current = &mut self.blocks[Self::index(pos)];
+		if *current != block {
+			*current = block;
+			self.versions.data = next_version();
+		}
+	}
+
+	pub fn versions(&self) -> ChunkVersions {
+		self.versions
+	}
+
+	// Called by the lighting pass once there is one
+	#[allow(dead_code)]
+	pub fn mark_light_changed(&mut self) {
+		self.versions.light = next_version();
+	}
+
+	pub fn mark_mesh_changed(&mut self) {
+		self.versions.mesh = next_version();
+	}
+}
+
+/// Every loaded chunk, keyed by chunk coordinates. A chunk at `pos` covers
+/// blocks `pos * CHUNK_SIZE` up to but not including `(pos + 1) * CHUNK_SIZE`.
+#[derive(Resource, Default)]
+pub struct VoxelWorld {
+	chunks: HashMap<IVec3, Chunk>,
+}
+
+impl VoxelWorld {
+	/// The chunk containing the block at `pos`.
+	pub fn chunk_pos(pos: IVec3) -> IVec3 {
+		pos.div_euclid(IVec3::splat(CHUNK_SIZE as i32))
+	}
+
+	/// Position of the block at `pos` within its chunk.
+	pub fn local_pos(pos: IVec3) -> UVec3 {
+		pos.rem_euclid(IVec3::splat(CHUNK_SIZE as i32)).as_uvec3()
+	}
+
+	pub fn chunk(&self, pos: IVec3) -> Option<&Chunk> {
+		self.chunks.get(&pos)
+	}
+
+	pub fn chunk_mut(&mut self, pos: IVec3) -> Option<&mut Chunk> {
+		self.chunks.get_mut(&pos)
+	}
+
+	pub fn chunks(&self) -> impl Iterator<Item = (IVec3, &Chunk)> {
+		self.chunks.iter().map(|(pos, chunk)| (*pos, chunk))
+	}
+
+	/// Get the block at world position `pos`, blocks in unloaded chunks are
+	/// air.
+	pub fn get_block(&self, pos: IVec3) -> BlockId {
+		self.chunk(Self::chunk_pos(pos))
+			.map_or(BlockId::AIR, |chunk| chunk.get(Self::local_pos(pos)))
+	}
+
+	/// Set the block at world position `pos`, creating its chunk if needed.
+	pub fn set_block(&mut self, pos: IVec3, block: BlockId) {
+		let chunk_pos = Self::chunk_pos(pos);
+		// No need to create a chunk just to fill it with air
+		if block.is_air() && !self.chunks.contains_key(&chunk_pos) {
+			return;
+		}
+		self.chunks
+			.entry(chunk_pos)
+			.or_default()
+			.set(Self::local_pos(pos), block);
+	}
+}
+
+/// Fill a few chunks around the origin with rolling hills so there is
+/// something to look at until there is a real world generator.
+pub fn generate_test_terrain(mut world: ResMut<VoxelWorld>, registry: Res<BlockRegistry>) {
+	let (Some(stone), Some(dirt), Some(grass)) = (
+		registry.id("stone"),
+		registry.id("dirt"),
+		registry.id("grass"),
+	) else {
+		bevy::log::warn!(
+			target: target::APP,
+			"Test terrain needs stone, dirt and grass blocks"
+		);
+		return;
+	};
+
+	let extent = CHUNK_SIZE as i32 * 2;
+	for x in -extent..extent {
+		for z in -extent..extent {
+			let height = 8.0 + 4.0 * (x as f32 / 8.0).sin() * (z as f32 / 11.0).cos();
+			let height = height as i32;
+			for y in 0..height {
+				let block = match height - y {
+					1 => grass,
+					2..=4 => dirt,
+					_ => stone,
+				};
+				world.set_block(IVec3::new(x, y, z), block);
+			}
+		}
+	}
+}